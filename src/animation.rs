@@ -0,0 +1,230 @@
+// animation.rs
+use std::collections::HashMap;
+use bevy::asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use crate::state::GameplaySet;
+
+// Plugin for sprite-sheet animations described by `*.sheet.ron` files next to their images
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SpriteSheet>()
+            .init_asset_loader::<SpriteSheetLoader>()
+            .init_resource::<Animations>()
+            .add_systems(Update, animate_sprites.in_set(GameplaySet))
+            // Runs after the spawning commands were applied, before the first frame is drawn
+            .add_systems(PostUpdate, start_sprite_animations);
+    }
+}
+
+// How a clip carries on after its last frame
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PlayMode {
+    Loop,
+    Once,
+    PingPong,
+}
+
+// A run of frames within a sheet
+#[derive(Deserialize, Clone)]
+pub struct AnimationClip {
+    pub first: usize,
+    pub last: usize,
+    pub fps: f32,
+    pub mode: PlayMode,
+    #[serde(default)]
+    pub despawn_on_finish: bool, // Only meaningful for `PlayMode::Once`
+}
+
+// Contents of a `*.sheet.ron` file
+#[derive(Deserialize)]
+struct SpriteSheetDef {
+    image: String, // Relative to the sheet file
+    tile_size: Vec2,
+    columns: usize,
+    rows: usize,
+    clips: HashMap<String, AnimationClip>,
+}
+
+// A loaded sprite sheet's named clips; its atlas is the `#atlas` sub-asset
+#[derive(TypeUuid, TypePath)]
+#[uuid = "0f6c2a3e-5b1d-4d8e-9a47-7c3e1f2b9d60"]
+pub struct SpriteSheet {
+    pub clips: HashMap<String, AnimationClip>,
+}
+
+#[derive(Default)]
+pub struct SpriteSheetLoader;
+
+impl AssetLoader for SpriteSheetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let def: SpriteSheetDef = ron::de::from_bytes(bytes)?;
+            // A clip that can't play is a mistake in the file; fail the load rather than panic in a system later
+            for (name, clip) in def.clips.iter() {
+                if !(clip.fps.is_finite() && clip.fps > 0.0) {
+                    return Err(bevy::asset::Error::msg(format!(
+                        "clip `{}` needs an fps above 0, not {}",
+                        name, clip.fps
+                    )));
+                }
+                if clip.first > clip.last {
+                    return Err(bevy::asset::Error::msg(format!(
+                        "clip `{}` starts at frame {} after its last frame {}",
+                        name, clip.first, clip.last
+                    )));
+                }
+            }
+            let image_path = AssetPath::new(load_context.path().with_file_name(&def.image), None);
+            let image = load_context.get_handle(image_path.clone());
+            let atlas = TextureAtlas::from_grid(image, def.tile_size, def.columns, def.rows, None, None);
+            load_context.set_labeled_asset("atlas", LoadedAsset::new(atlas).with_dependency(image_path));
+            load_context.set_default_asset(LoadedAsset::new(SpriteSheet { clips: def.clips }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheet.ron"]
+    }
+}
+
+// Handles to a sheet and its atlas; the atlas handle is usable before the sheet finishes loading
+#[derive(Clone)]
+pub struct SheetHandles {
+    pub sheet: Handle<SpriteSheet>,
+    pub atlas: Handle<TextureAtlas>,
+}
+
+impl SheetHandles {
+    fn load(asset_server: &AssetServer, path: &str) -> Self {
+        SheetHandles {
+            sheet: asset_server.load(path),
+            atlas: asset_server.load(format!("{}#atlas", path)),
+        }
+    }
+}
+
+// Every animated sheet in the game, loaded at start-up
+#[derive(Resource)]
+pub struct Animations {
+    pub fireball: SheetHandles,
+    pub thruster: SheetHandles,
+    pub laser_impact: SheetHandles,
+}
+
+impl FromWorld for Animations {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Animations {
+            fireball: SheetHandles::load(asset_server, "fireball.sheet.ron"),
+            thruster: SheetHandles::load(asset_server, "thruster.sheet.ron"),
+            laser_impact: SheetHandles::load(asset_server, "laser_impact.sheet.ron"),
+        }
+    }
+}
+
+// Plays a named clip of a sprite sheet on a `TextureAtlasSprite`
+#[derive(Component)]
+pub struct SpriteAnimation {
+    pub sheet: Handle<SpriteSheet>,
+    pub clip: String,
+    timer: Timer,
+    started: bool,
+    reversing: bool, // Ping-pong clips running backwards
+}
+
+impl SpriteAnimation {
+    pub fn new(sheet: &SheetHandles, clip: &str) -> Self {
+        SpriteAnimation {
+            sheet: sheet.sheet.clone(),
+            clip: clip.to_string(),
+            timer: Timer::default(),
+            started: false,
+            reversing: false,
+        }
+    }
+}
+
+// Builds an animated sprite playing `clip`
+pub fn animated_sprite(sheet: &SheetHandles, clip: &str, transform: Transform) -> impl Bundle {
+    (
+        SpriteSheetBundle {
+            texture_atlas: sheet.atlas.clone(),
+            transform,
+            ..Default::default()
+        },
+        SpriteAnimation::new(sheet, clip),
+    )
+}
+
+// System to put new animations on the first frame of their clip once the sheet is loaded
+pub fn start_sprite_animations(
+    sheets: Res<Assets<SpriteSheet>>,
+    mut query: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        if animation.started {
+            continue;
+        }
+        let Some(clip) = sheets.get(&animation.sheet).and_then(|sheet| sheet.clips.get(&animation.clip)) else {
+            continue;
+        };
+        sprite.index = clip.first;
+        animation.timer = Timer::from_seconds(1.0 / clip.fps, TimerMode::Repeating);
+        animation.started = true;
+    }
+}
+
+// System to step every started animation through its clip
+pub fn animate_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    sheets: Res<Assets<SpriteSheet>>,
+    mut query: Query<(Entity, &mut SpriteAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut animation, mut sprite) in query.iter_mut() {
+        if !animation.started {
+            continue;
+        }
+        let Some(clip) = sheets.get(&animation.sheet).and_then(|sheet| sheet.clips.get(&animation.clip)) else {
+            continue;
+        };
+
+        animation.timer.tick(time.delta());
+        for _ in 0..animation.timer.times_finished_this_tick() {
+            match clip.mode {
+                PlayMode::Loop => {
+                    sprite.index = if sprite.index >= clip.last { clip.first } else { sprite.index + 1 };
+                }
+                PlayMode::Once => {
+                    if sprite.index < clip.last {
+                        sprite.index += 1;
+                    } else if clip.despawn_on_finish {
+                        commands.entity(entity).despawn_recursive();
+                        break;
+                    }
+                }
+                PlayMode::PingPong => {
+                    if clip.first == clip.last {
+                        continue;
+                    }
+                    if sprite.index >= clip.last {
+                        animation.reversing = true;
+                    } else if sprite.index <= clip.first {
+                        animation.reversing = false;
+                    }
+                    sprite.index = if animation.reversing { sprite.index - 1 } else { sprite.index + 1 };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// audio.rs
+use std::f32::consts::PI;
+use bevy::audio::Volume;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use crate::component::{Fireball, LaserType, LevelEntity, Ship, Thrust};
+use crate::input::LaserFired;
+use crate::results::RunOutcome;
+use crate::settings::Settings;
+use crate::state::{AppState, PauseState};
+use crate::system::BoxDestroyed;
+
+// Loudness of each sound relative to its channel volume
+const ENGINE_VOLUME: f32 = 0.5;
+const LASER_VOLUME: f32 = 0.6;
+const EXPLOSION_VOLUME: f32 = 0.9;
+const BOX_BREAK_VOLUME: f32 = 0.7;
+const STINGER_VOLUME: f32 = 1.0;
+
+// World distance heard at full volume; further away, in-world sounds fall off with the square of the distance
+const FULL_VOLUME_DISTANCE: f32 = 250.0;
+
+// Distance between the listener's ears, in units of `FULL_VOLUME_DISTANCE`
+const EAR_GAP: f32 = 1.0;
+
+// Plugin for background music, the ship's engine and sound effects
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundAssets>()
+            .add_systems(Startup, start_music)
+            .add_systems(OnEnter(AppState::InGame), spawn_engine_sound)
+            .add_systems(OnEnter(AppState::Results), play_outcome_stinger)
+            .add_systems(
+                Update,
+                (
+                    update_engine_sound.run_if(in_state(AppState::InGame)), // Engine hums while the ship is steered
+                    play_laser_sounds,                                      // One shot per laser fired
+                    play_explosion_sounds,                                  // One bang per fireball
+                    play_box_break_sounds,                                  // One crunch per destroyed box
+                    follow_listener,                                        // Keep in-world sounds panned as the listener moves
+                    apply_volume_settings.run_if(resource_changed::<Settings>()), // Follow the volume sliders
+                ),
+            );
+    }
+}
+
+// Handles to every sound the game plays
+#[derive(Resource)]
+pub struct SoundAssets {
+    pub music: Handle<AudioSource>,
+    pub engine: Handle<AudioSource>,
+    pub laser_a: Handle<AudioSource>,
+    pub laser_b: Handle<AudioSource>,
+    pub explosion: Handle<AudioSource>,
+    pub box_break: Handle<AudioSource>,
+    pub level_complete: Handle<AudioSource>,
+    pub game_over: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SoundAssets {
+            music: asset_server.load("sounds/music.wav"),
+            engine: asset_server.load("sounds/move.mp3"),
+            laser_a: asset_server.load("sounds/laser_a.wav"),
+            laser_b: asset_server.load("sounds/laser_b.wav"),
+            explosion: asset_server.load("sounds/explosion.wav"),
+            box_break: asset_server.load("sounds/box_break.wav"),
+            level_complete: asset_server.load("sounds/level_complete.wav"),
+            game_over: asset_server.load("sounds/game_over.wav"),
+        }
+    }
+}
+
+// Looping background music, playing from start-up on
+#[derive(Component)]
+pub struct Music;
+
+// Looping engine sound of the current level, paused whenever the ship isn't being steered
+#[derive(Component)]
+pub struct EngineSound;
+
+// One-shot sound placed in the world, panned and attenuated relative to the listener
+#[derive(Component)]
+pub struct SpatialSound;
+
+// Where in-world sounds are heard from: the ship while there is just one, otherwise the camera
+#[derive(SystemParam)]
+pub struct Listener<'w, 's> {
+    ships: Query<'w, 's, &'static Transform, With<Ship>>,
+    cameras: Query<'w, 's, &'static Transform, (With<Camera2d>, Without<Ship>)>,
+}
+
+impl Listener<'_, '_> {
+    pub fn position(&self) -> Vec3 {
+        self.ships
+            .get_single()
+            .or_else(|_| self.cameras.get_single())
+            .map(|transform| transform.translation)
+            .unwrap_or(Vec3::ZERO)
+    }
+
+    // Listener placement in audio space. rodio 0.17 gives each ear the other ear's volume,
+    // so the listener is turned around to keep sounds on the right in the right speaker
+    fn transform(&self) -> Transform {
+        Transform::from_translation(to_audio_space(self.position())).with_rotation(Quat::from_rotation_y(PI))
+    }
+}
+
+// Maps a world position onto the flat audio space used by the spatial sinks
+fn to_audio_space(position: Vec3) -> Vec3 {
+    position.truncate().extend(0.0) / FULL_VOLUME_DISTANCE
+}
+
+// Plays a one-shot sound effect that cleans itself up when done
+pub fn play_sfx(commands: &mut Commands, source: Handle<AudioSource>, volume: f32) {
+    commands.spawn(AudioBundle {
+        source,
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(volume)),
+    });
+}
+
+// Plays a one-shot sound effect coming from `emitter` in the world
+pub fn play_spatial_sfx(
+    commands: &mut Commands,
+    source: Handle<AudioSource>,
+    volume: f32,
+    emitter: Vec3,
+    listener: &Listener,
+) {
+    commands.spawn((
+        SpatialAudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_absolute(volume)),
+            spatial: SpatialSettings::new(listener.transform(), EAR_GAP, to_audio_space(emitter)),
+        },
+        SpatialSound,
+    ));
+}
+
+// System to start the background music
+pub fn start_music(mut commands: Commands, sounds: Res<SoundAssets>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioBundle {
+            source: sounds.music.clone(),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_absolute(settings.music_level())),
+        },
+        Music,
+    ));
+}
+
+// System to add the engine sound to a new level, starting out paused
+pub fn spawn_engine_sound(mut commands: Commands, sounds: Res<SoundAssets>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioBundle {
+            source: sounds.engine.clone(),
+            settings: PlaybackSettings::LOOP
+                .paused()
+                .with_volume(Volume::new_absolute(settings.sfx_level() * ENGINE_VOLUME)),
+        },
+        EngineSound,
+        LevelEntity,
+    ));
+}
+
+// System to play the engine sound only while the ship is moving and the game isn't paused
+pub fn update_engine_sound(
+    pause_state: Res<State<PauseState>>,
+    ships: Query<&Thrust, With<Ship>>,
+    engine: Query<&AudioSink, With<EngineSound>>,
+) {
+    let thrusting = *pause_state.get() == PauseState::Running && ships.iter().any(|thrust| thrust.0 != Vec3::ZERO);
+    for sink in engine.iter() {
+        if thrusting && sink.is_paused() {
+            sink.play();
+        } else if !thrusting && !sink.is_paused() {
+            sink.pause();
+        }
+    }
+}
+
+// System to play a laser sound matching the type of each laser fired
+pub fn play_laser_sounds(
+    mut commands: Commands,
+    mut laser_fired: EventReader<LaserFired>,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    for event in laser_fired.iter() {
+        let source = match event.laser_type {
+            LaserType::A => sounds.laser_a.clone(),
+            LaserType::B => sounds.laser_b.clone(),
+        };
+        play_sfx(&mut commands, source, settings.sfx_level() * LASER_VOLUME);
+    }
+}
+
+// System to play an explosion where every new fireball appears
+pub fn play_explosion_sounds(
+    mut commands: Commands,
+    fireballs: Query<&Transform, Added<Fireball>>,
+    listener: Listener,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    for transform in fireballs.iter() {
+        play_spatial_sfx(
+            &mut commands,
+            sounds.explosion.clone(),
+            settings.sfx_level() * EXPLOSION_VOLUME,
+            transform.translation,
+            &listener,
+        );
+    }
+}
+
+// System to play a crunch where each box was destroyed
+pub fn play_box_break_sounds(
+    mut commands: Commands,
+    mut box_destroyed: EventReader<BoxDestroyed>,
+    listener: Listener,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    for event in box_destroyed.iter() {
+        play_spatial_sfx(
+            &mut commands,
+            sounds.box_break.clone(),
+            settings.sfx_level() * BOX_BREAK_VOLUME,
+            event.translation,
+            &listener,
+        );
+    }
+}
+
+// System to move the ears of in-world sounds still playing along with the listener
+pub fn follow_listener(listener: Listener, sinks: Query<&SpatialAudioSink, With<SpatialSound>>) {
+    if sinks.is_empty() {
+        return;
+    }
+    let transform = listener.transform();
+    for sink in sinks.iter() {
+        sink.set_listener_position(transform, EAR_GAP);
+    }
+}
+
+// System to play the level-complete or game-over stinger
+pub fn play_outcome_stinger(
+    mut commands: Commands,
+    outcome: Res<RunOutcome>,
+    sounds: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    let source = match *outcome {
+        RunOutcome::LevelComplete => sounds.level_complete.clone(),
+        RunOutcome::GameOver => sounds.game_over.clone(),
+    };
+    play_sfx(&mut commands, source, settings.sfx_level() * STINGER_VOLUME);
+}
+
+// System to apply volume changes to the sounds that keep playing
+pub fn apply_volume_settings(
+    settings: Res<Settings>,
+    music: Query<&AudioSink, With<Music>>,
+    engine: Query<&AudioSink, With<EngineSound>>,
+) {
+    for sink in music.iter() {
+        sink.set_volume(settings.music_level());
+    }
+    for sink in engine.iter() {
+        sink.set_volume(settings.sfx_level() * ENGINE_VOLUME);
+    }
+}
@@ -0,0 +1,386 @@
+// boss.rs
+use std::f32::consts::{FRAC_PI_2, TAU};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use crate::animation::Animations;
+use crate::component::{EndPoint, Faction, GameTimer, Invulnerable, Laser, LaserType, LevelEntity, Locked, Ship};
+use crate::config::{BossConfig, GameConfig};
+use crate::input::spawn_laser;
+use crate::profile::{LifeLost, PlayerProfile};
+use crate::state::GameplaySet;
+use crate::system::{destroy_ship, laser_hit, nearest_ship, spawn_fireball, LaserImpact};
+
+// Weak points on the hull, relative to the boss, and the laser each one is hurt by
+const WEAK_POINTS: [(Vec2, LaserType); 4] = [
+    (Vec2::new(-80.0, -10.0), LaserType::A), // Left wing
+    (Vec2::new(80.0, -10.0), LaserType::B),  // Right wing
+    (Vec2::new(0.0, 70.0), LaserType::A),    // Nose
+    (Vec2::new(0.0, -75.0), LaserType::B),   // Engine
+];
+
+// Health of each weak point unless the level says otherwise
+fn default_weak_point_health() -> u32 {
+    8
+}
+
+// Distance at which lasers hit the hull, and at which the boss rams the ship; the weak points' radius is in
+// the game config
+const HULL_RADIUS: f32 = 90.0;
+
+// Gap between the explosions of the defeat sequence
+const DEFEAT_EXPLOSION_SECONDS: f32 = 0.2;
+
+// Plugin for level bosses: their phases, attacks, weak points and defeat
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                move_boss,         // Sweep back and forth, closing in during the last phase
+                boss_attack,       // Fire the current phase's pattern at the ship
+                damage_boss,       // Lasers of the right type wear down the weak points
+                ram_ship,          // Flying into the hull costs a life
+                update_boss_phase, // Health thresholds move the boss on to its next phase
+                tint_weak_points,  // Weak points show their laser type until destroyed
+                run_boss_defeat,   // Blow the boss up and open the end point
+            )
+                .chain()
+                .in_set(GameplaySet),
+        );
+    }
+}
+
+// A boss placed in a level file; its level's end point stays locked until it is defeated
+#[derive(Deserialize, Clone)]
+pub struct BossSpawn {
+    pub position: Vec2,
+    #[serde(default = "default_weak_point_health")]
+    pub weak_point_health: u32,
+}
+
+// The boss's attack phases, from full health down
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BossPhase {
+    Aimed,  // Single shots at the ship
+    Spread, // Fans of three shots
+    Ring,   // Rings of shots all around, while closing in
+}
+
+impl BossPhase {
+    fn for_health(fraction: f32, config: &BossConfig) -> Self {
+        if fraction > config.spread_health {
+            BossPhase::Aimed
+        } else if fraction > config.ring_health {
+            BossPhase::Spread
+        } else {
+            BossPhase::Ring
+        }
+    }
+
+    pub fn number(self) -> u32 {
+        match self {
+            BossPhase::Aimed => 1,
+            BossPhase::Spread => 2,
+            BossPhase::Ring => 3,
+        }
+    }
+
+    // Seconds between attacks
+    fn fire_interval(self) -> f32 {
+        match self {
+            BossPhase::Aimed => 1.2,
+            BossPhase::Spread => 1.0,
+            BossPhase::Ring => 1.4,
+        }
+    }
+
+    // Sweeps per second
+    fn sweep_speed(self) -> f32 {
+        match self {
+            BossPhase::Aimed => 0.15,
+            BossPhase::Spread => 0.25,
+            BossPhase::Ring => 0.35,
+        }
+    }
+}
+
+// Root of the boss; its hull and weak points are children
+#[derive(Component)]
+pub struct Boss {
+    pub home: Vec2, // Centre of its sweep
+    pub phase: BossPhase,
+    pub defeat: Option<Timer>, // Running once every weak point is destroyed
+    finisher: usize,           // Player whose laser last damaged a weak point, who scores the defeat
+    elapsed: f32,
+    fire_timer: Timer,
+    explosion_timer: Timer,
+}
+
+// Part of the boss that only takes damage from lasers of `laser_type`
+#[derive(Component)]
+pub struct WeakPoint {
+    pub index: usize, // Position in `WEAK_POINTS`
+    pub laser_type: LaserType,
+    pub health: u32,
+    pub max_health: u32,
+}
+
+// Health left and total health over all weak points
+pub fn boss_health<'a>(weak_points: impl Iterator<Item = &'a WeakPoint>) -> (u32, u32) {
+    weak_points.fold((0, 0), |(health, max), point| (health + point.health, max + point.max_health))
+}
+
+// Spawns the level's boss
+pub fn spawn_boss(commands: &mut Commands, asset_server: &AssetServer, config: &BossConfig, spawn: &BossSpawn) {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(spawn.position.extend(0.5))),
+            Boss {
+                home: spawn.position,
+                phase: BossPhase::Aimed,
+                defeat: None,
+                elapsed: 0.0,
+                fire_timer: Timer::from_seconds(BossPhase::Aimed.fire_interval(), TimerMode::Repeating),
+                explosion_timer: Timer::from_seconds(DEFEAT_EXPLOSION_SECONDS, TimerMode::Repeating),
+                finisher: 0,
+            },
+            LevelEntity,
+        ))
+        .with_children(|boss| {
+            // Hull, facing down toward the play area
+            boss.spawn(SpriteBundle {
+                texture: asset_server.load("ship.png"),
+                sprite: Sprite {
+                    color: Color::rgb(0.6, 0.6, 0.7),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    scale: Vec3::new(0.22, 0.22, 1.0),
+                    rotation: Quat::from_rotation_z(std::f32::consts::PI),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            for (index, (offset, laser_type)) in WEAK_POINTS.into_iter().enumerate() {
+                boss.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: laser_type.color(),
+                            custom_size: Some(Vec2::splat(config.weak_point_radius)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(offset.extend(0.1))
+                            .with_rotation(Quat::from_rotation_z(FRAC_PI_2 / 2.0)),
+                        ..Default::default()
+                    },
+                    WeakPoint {
+                        index,
+                        laser_type,
+                        health: spawn.weak_point_health,
+                        max_health: spawn.weak_point_health,
+                    },
+                ));
+            }
+        });
+}
+
+// Rotation that sends a laser along `direction`
+fn aim(direction: Vec2) -> Quat {
+    Quat::from_rotation_z(direction.y.atan2(direction.x) - FRAC_PI_2)
+}
+
+// System to sweep the boss from side to side, closing in on the nearest ship during its last phase
+pub fn move_boss(
+    time: Res<Time>,
+    game_timer: Res<GameTimer>,
+    ships: Query<&Transform, With<Ship>>,
+    mut bosses: Query<(&mut Boss, &mut Transform), Without<Ship>>,
+    config: Res<GameConfig>,
+) {
+    // The boss stops with everything else once the game is over
+    if game_timer.1 {
+        return;
+    }
+    for (mut boss, mut transform) in bosses.iter_mut() {
+        if boss.defeat.is_some() {
+            continue;
+        }
+        boss.elapsed += time.delta_seconds() * boss.phase.sweep_speed();
+        if let (BossPhase::Ring, Some(ship)) = (boss.phase, nearest_ship(ships.iter(), boss.home)) {
+            let to_ship = ship - boss.home;
+            boss.home += to_ship.clamp_length_max(config.boss.pursuit_speed * time.delta_seconds());
+        }
+        let sweep = Vec2::new((boss.elapsed * TAU).sin() * config.boss.sweep_distance, 0.0);
+        transform.translation = (boss.home + sweep).extend(transform.translation.z);
+    }
+}
+
+// System to fire the current phase's attack at the nearest ship while it is in range
+pub fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    ships: Query<&Transform, With<Ship>>,
+    mut bosses: Query<(&mut Boss, &Transform), Without<Ship>>,
+    config: Res<GameConfig>,
+) {
+    for (mut boss, transform) in bosses.iter_mut() {
+        let Some(ship) = nearest_ship(ships.iter(), transform.translation.truncate()) else {
+            continue;
+        };
+        let to_ship = ship - transform.translation.truncate();
+        if boss.defeat.is_some() || to_ship.length() > config.boss.engage_range {
+            continue;
+        }
+        if !boss.fire_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let directions: Vec<Vec2> = match boss.phase {
+            BossPhase::Aimed => vec![to_ship],
+            BossPhase::Spread => [-0.3, 0.0, 0.3].iter().map(|angle| Vec2::from_angle(*angle).rotate(to_ship)).collect(),
+            BossPhase::Ring => (0..12).map(|i| Vec2::from_angle(i as f32 / 12.0 * TAU).rotate(to_ship)).collect(),
+        };
+        for direction in directions {
+            spawn_laser(
+                &mut commands,
+                &asset_server,
+                transform.translation,
+                aim(direction),
+                LaserType::B,
+                Faction::Enemy,
+            );
+        }
+    }
+}
+
+// System to damage weak points hit by lasers of their type; other hits glance off the armour
+pub fn damage_boss(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    lasers: Query<(Entity, &Transform, &Laser)>,
+    mut bosses: Query<(&mut Boss, &GlobalTransform)>,
+    mut weak_points: Query<(&mut WeakPoint, &GlobalTransform, &Parent)>,
+    mut laser_impact: EventWriter<LaserImpact>,
+    config: Res<GameConfig>,
+) {
+    for (laser_entity, laser_transform, laser) in lasers.iter() {
+        let Faction::Player(player) = laser.faction else {
+            continue;
+        };
+        let position = laser_transform.translation.truncate();
+        let weak_point = weak_points.iter_mut().find(|(point, transform, _)| {
+            point.health > 0 && transform.translation().truncate().distance(position) < config.boss.weak_point_radius
+        });
+        if let Some((mut point, _, parent)) = weak_point {
+            if point.laser_type == laser.laser_type {
+                point.health -= 1;
+                if let Ok((mut boss, _)) = bosses.get_mut(parent.get()) {
+                    boss.finisher = player;
+                }
+            }
+            laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
+        } else if bosses.iter().any(|(boss, transform)| {
+            boss.defeat.is_none() && transform.translation().truncate().distance(position) < HULL_RADIUS
+        }) {
+            laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
+        }
+    }
+}
+
+// System to take a life when a ship flies into the boss
+pub fn ram_ship(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    ships: Query<(Entity, &Ship, &Transform), Without<Invulnerable>>,
+    bosses: Query<(&Boss, &Transform), Without<Ship>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut life_lost: EventWriter<LifeLost>,
+) {
+    for (ship_entity, ship, ship_transform) in ships.iter() {
+        let rammed = bosses.iter().any(|(boss, transform)| {
+            boss.defeat.is_none() && transform.translation.distance(ship_transform.translation) < HULL_RADIUS
+        });
+        if rammed {
+            spawn_fireball(&mut commands, &animations, ship_transform.translation);
+            destroy_ship(&mut commands, &mut profile, &mut life_lost, ship_entity, ship.0);
+        }
+    }
+}
+
+// System to move the boss on to the phase for its health, and start its defeat once it has none left
+pub fn update_boss_phase(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    mut bosses: Query<(&mut Boss, &Transform, &Children)>,
+    weak_points: Query<(&WeakPoint, &GlobalTransform)>,
+    config: Res<GameConfig>,
+) {
+    for (mut boss, transform, children) in bosses.iter_mut() {
+        if boss.defeat.is_some() {
+            continue;
+        }
+        let (health, max_health) = boss_health(weak_points.iter_many(children).map(|(point, _)| point));
+        if health == 0 {
+            boss.defeat = Some(Timer::from_seconds(config.boss.defeat_seconds, TimerMode::Once));
+            spawn_fireball(&mut commands, &animations, transform.translation);
+            continue;
+        }
+        let phase = BossPhase::for_health(health as f32 / max_health as f32, &config.boss);
+        if phase != boss.phase {
+            boss.phase = phase;
+            boss.fire_timer = Timer::from_seconds(phase.fire_interval(), TimerMode::Repeating);
+            // Mark the change with a blast from every weak point still standing
+            for (point, point_transform) in weak_points.iter_many(children) {
+                if point.health > 0 {
+                    spawn_fireball(&mut commands, &animations, point_transform.translation());
+                }
+            }
+        }
+    }
+}
+
+// System to dim weak points once they are destroyed
+pub fn tint_weak_points(mut weak_points: Query<(&WeakPoint, &mut Sprite), Changed<WeakPoint>>) {
+    for (point, mut sprite) in weak_points.iter_mut() {
+        sprite.color = if point.health > 0 { point.laser_type.color() } else { Color::rgb(0.2, 0.2, 0.2) };
+    }
+}
+
+// System to play the boss's defeat: a string of explosions, then the boss goes and the end point opens
+pub fn run_boss_defeat(
+    mut commands: Commands,
+    time: Res<Time>,
+    animations: Res<Animations>,
+    mut bosses: Query<(Entity, &mut Boss, &Transform)>,
+    mut end_points: Query<(Entity, &mut Sprite), (With<EndPoint>, With<Locked>)>,
+    mut profile: ResMut<PlayerProfile>,
+    config: Res<GameConfig>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, mut boss, transform) in bosses.iter_mut() {
+        let boss = &mut *boss;
+        let Some(defeat) = boss.defeat.as_mut() else {
+            continue;
+        };
+        defeat.tick(time.delta());
+        if boss.explosion_timer.tick(time.delta()).just_finished() {
+            let offset = Vec2::new(rng.gen_range(-HULL_RADIUS..HULL_RADIUS), rng.gen_range(-HULL_RADIUS..HULL_RADIUS));
+            spawn_fireball(&mut commands, &animations, transform.translation + offset.extend(1.0));
+        }
+        if defeat.finished() {
+            commands.entity(entity).despawn_recursive();
+            spawn_fireball(&mut commands, &animations, transform.translation);
+            let stats = &mut profile.players[boss.finisher];
+            stats.score += config.boss.score;
+            stats.kills += 1;
+            for (end_point, mut sprite) in end_points.iter_mut() {
+                commands.entity(end_point).remove::<Locked>();
+                sprite.color = Color::RED;
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// camera.rs
+use bevy::prelude::*;
+use crate::display::ViewBounds;
+use crate::component::{Fireball, LevelEntity, Ship, Thrust};
+use crate::level::WorldBounds;
+use crate::profile::LifeLost;
+use crate::settings::Settings;
+use crate::state::{AppState, GameplaySet};
+
+// How far the ship may drift from the centre of the view, per axis, before the camera follows
+const DEAD_ZONE: Vec2 = Vec2::new(80.0, 60.0);
+
+// How far ahead of the ship the camera looks in the direction it is flying
+const LOOK_AHEAD: f32 = 120.0;
+
+// How quickly the camera catches up and the look-ahead swings round; higher is snappier
+const FOLLOW_SHARPNESS: f32 = 5.0;
+const LOOK_AHEAD_SHARPNESS: f32 = 2.0;
+
+// Trauma added by a lost life and by each explosion; shake grows with the square of the trauma
+const HIT_TRAUMA: f32 = 0.6;
+const EXPLOSION_TRAUMA: f32 = 0.3;
+
+// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+
+// Camera offset and roll at full trauma
+const MAX_SHAKE_OFFSET: f32 = 16.0;
+const MAX_SHAKE_ROLL: f32 = 0.05;
+
+// Game speed during a hit-stop and how long it lasts, in real seconds
+const HIT_STOP_SPEED: f32 = 0.05;
+const HIT_STOP_SECONDS: f32 = 0.12;
+
+// Starting opacity and length of the flash on a lost life
+const FLASH_ALPHA: f32 = 0.6;
+const FLASH_SECONDS: f32 = 0.25;
+
+// Plugin for the camera: following the ship around the world, plus screen shake, hit-stop and screen flash
+pub struct GameCameraPlugin;
+
+impl Plugin for GameCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraFollow>()
+            .init_resource::<CameraShake>()
+            .init_resource::<HitStop>()
+            .add_systems(
+                Update,
+                (
+                    trigger_camera_effects, // Hits and explosions kick off the effects
+                    follow_ship,            // Keep the ship in view, within the world
+                    shake_camera,           // Shake the camera while there is trauma
+                    end_hit_stop,           // Bring the game back up to speed
+                    fade_screen_flash,      // Fade the flash out
+                )
+                    .chain()
+                    .after(GameplaySet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_camera_effects);
+    }
+}
+
+// Point the camera is centred on before shaking (unset until the first frame of a level) and its current look-ahead
+#[derive(Resource, Default)]
+pub struct CameraFollow {
+    focus: Option<Vec2>,
+    look_ahead: Vec2,
+}
+
+// Current trauma (0.0 to 1.0) and the shake currently applied on top of the camera's own placement
+#[derive(Resource, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+    offset: Vec3,
+    roll: f32,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+// Real time left on the current hit-stop, if any
+#[derive(Resource, Default)]
+pub struct HitStop(pub Option<Timer>);
+
+// Full-screen flash overlay
+#[derive(Component)]
+pub struct ScreenFlash(pub Timer);
+
+// System to start the effects enabled in the settings for lost lives and explosions
+pub fn trigger_camera_effects(
+    mut commands: Commands,
+    mut life_lost: EventReader<LifeLost>,
+    fireballs: Query<(), Added<Fireball>>,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time>,
+) {
+    let hit = life_lost.iter().count() > 0;
+    let explosions = fireballs.iter().count();
+
+    if settings.screen_shake {
+        if hit {
+            shake.add_trauma(HIT_TRAUMA);
+        }
+        shake.add_trauma(EXPLOSION_TRAUMA * explosions as f32);
+    }
+
+    if hit && settings.hit_stop {
+        time.set_relative_speed(HIT_STOP_SPEED);
+        hit_stop.0 = Some(Timer::from_seconds(HIT_STOP_SECONDS, TimerMode::Once));
+    }
+
+    if hit && settings.screen_flash {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(1.0, 1.0, 1.0, FLASH_ALPHA).into(),
+                z_index: ZIndex::Global(5), // Over the HUD, under the menus
+                ..Default::default()
+            },
+            ScreenFlash(Timer::from_seconds(FLASH_SECONDS, TimerMode::Once)),
+            LevelEntity,
+        ));
+    }
+}
+
+// Share of the remaining distance to cover this frame when easing with the given sharpness
+fn smoothing(sharpness: f32, delta_seconds: f32) -> f32 {
+    1.0 - (-sharpness * delta_seconds).exp()
+}
+
+// System to ease the camera after the ship, or the middle of the ships in co-op, keeping the view inside the world
+pub fn follow_ship(
+    time: Res<Time>,
+    bounds: Res<WorldBounds>,
+    view: Res<ViewBounds>,
+    shake: Res<CameraShake>,
+    mut follow: ResMut<CameraFollow>,
+    ships: Query<(&Transform, &Thrust), With<Ship>>,
+    mut cameras: Query<&mut Transform, (With<Camera2d>, Without<Ship>)>,
+) {
+    let Ok(mut camera_transform) = cameras.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_seconds();
+
+    let ship_count = ships.iter().len();
+    if ship_count > 0 {
+        let (position, thrust) = ships
+            .iter()
+            .fold((Vec2::ZERO, Vec2::ZERO), |(position, thrust), (ship_transform, ship_thrust)| {
+                (position + ship_transform.translation.truncate(), thrust + ship_thrust.0.truncate())
+            });
+        let (position, thrust) = (position / ship_count as f32, thrust / ship_count as f32);
+
+        let look_ahead = follow.look_ahead;
+        follow.look_ahead = look_ahead.lerp(thrust * LOOK_AHEAD, smoothing(LOOK_AHEAD_SHARPNESS, delta));
+        let target = position + follow.look_ahead;
+
+        // Jump straight to the ship on a level's first frame, then only chase it out of the dead zone
+        follow.focus = Some(match follow.focus {
+            None => target,
+            Some(focus) => {
+                let offset = target - focus;
+                let desired = target - offset.clamp(-DEAD_ZONE, DEAD_ZONE);
+                focus.lerp(desired, smoothing(FOLLOW_SHARPNESS, delta))
+            }
+        });
+    }
+
+    let Some(focus) = follow.focus else {
+        return;
+    };
+
+    // Stop at the world edges; a world smaller than the view stays centred
+    let limit = (bounds.half_size - view.half_size).max(Vec2::ZERO);
+    let focus = focus.clamp(-limit, limit);
+    follow.focus = Some(focus);
+
+    camera_transform.translation = (focus + shake.offset.truncate()).extend(camera_transform.translation.z);
+}
+
+// System to shake the camera by its trauma, decaying in real time so hit-stops don't freeze it
+pub fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    if shake.trauma == 0.0 && shake.offset == Vec3::ZERO {
+        return;
+    }
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * time.raw_delta_seconds()).max(0.0);
+
+    // Layered sines give a smooth but irregular wobble
+    let amount = shake.trauma * shake.trauma;
+    let t = time.raw_elapsed_seconds();
+    let offset = Vec3::new(
+        (t * 37.0).sin() * 0.7 + (t * 71.0).sin() * 0.3,
+        (t * 43.0).cos() * 0.7 + (t * 59.0).sin() * 0.3,
+        0.0,
+    ) * MAX_SHAKE_OFFSET
+        * amount;
+    let roll = (t * 29.0).sin() * MAX_SHAKE_ROLL * amount;
+
+    for mut transform in cameras.iter_mut() {
+        transform.translation += offset - shake.offset;
+        transform.rotate_z(roll - shake.roll);
+    }
+    shake.offset = offset;
+    shake.roll = roll;
+}
+
+// System to restore the game speed once the hit-stop is over
+pub fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut time: ResMut<Time>) {
+    let raw_delta = time.raw_delta();
+    if let Some(timer) = hit_stop.0.as_mut() {
+        if timer.tick(raw_delta).finished() {
+            time.set_relative_speed(1.0);
+            hit_stop.0 = None;
+        }
+    }
+}
+
+// System to fade out and remove the screen flash
+pub fn fade_screen_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut ScreenFlash, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut color) in flashes.iter_mut() {
+        flash.0.tick(time.raw_delta());
+        color.0.set_a(FLASH_ALPHA * flash.0.percent_left());
+        if flash.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// System to centre the camera and settle the game speed when leaving a level
+pub fn reset_camera_effects(
+    mut follow: ResMut<CameraFollow>,
+    mut shake: ResMut<CameraShake>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    for mut transform in cameras.iter_mut() {
+        transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+    }
+    *follow = CameraFollow::default();
+    *shake = CameraShake::default();
+
+    if hit_stop.0.take().is_some() {
+        time.set_relative_speed(1.0);
+    }
+}
@@ -1,9 +1,31 @@
 // component.rs
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-// Starship component
+// Starship component, with the index of the player flying it
+#[derive(Component, Clone, Copy)]
+pub struct Ship(pub usize);
+
+// Tints telling the players' ships and lasers apart; the first player keeps the original colors
+const PLAYER_COLORS: [Color; 2] = [Color::WHITE, Color::rgb(1.0, 0.7, 0.35)];
+
+impl Ship {
+    pub fn color(self) -> Color {
+        PLAYER_COLORS[self.0 % PLAYER_COLORS.len()]
+    }
+}
+
+// Ship that can't be hurt until its timer runs out; versus ships get it whenever they spawn
 #[derive(Component)]
-pub struct Ship;
+pub struct Invulnerable(pub Timer);
+
+// Direction the ship is being steered in this frame; zero when it is coasting
+#[derive(Component, Default)]
+pub struct Thrust(pub Vec3);
+
+// Engine flame attached to the ship
+#[derive(Component)]
+pub struct Thruster;
 
 // Box entity component
 #[derive(Component)]
@@ -25,50 +47,50 @@ pub struct StartPoint;
 #[derive(Component)]
 pub struct EndPoint;
 
+// Marks an end point that can't be reached until the level's boss is defeated
+#[derive(Component)]
+pub struct Locked;
+
+// Marker component for everything spawned for the current level, despawned when it is left
+#[derive(Component)]
+pub struct LevelEntity;
+
 // Game timer resource
 #[derive(Resource, Component)]
 pub struct GameTimer(pub Option<f32>, pub bool); // Option<f32> for elapsed time, bool to stop the timer
 
-// Starship lives resource
-#[derive(Resource, Component)]
-pub struct ShipLives(pub u32);
-
 #[derive(Component)]
 pub struct Laser {
-    #[allow(dead_code)]
-    pub laser_type: LaserType, // Suppress warning if not currently used
+    pub laser_type: LaserType,
+    pub faction: Faction, // Lasers only hurt the other side
+}
+
+// Side a laser was fired by
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Faction {
+    Player(usize), // Index of the player who fired it, who scores what it destroys
+    Enemy,
+}
+
+impl Default for Faction {
+    fn default() -> Self {
+        Faction::Player(0)
+    }
 }
 
 // Enum for different types of lasers
-#[derive(Component)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LaserType {
     A, // Corresponds to laser type A
     B, // Corresponds to laser type B
 }
 
-// Fireball sprite atlas for explosion
-#[derive(Resource)]
-pub struct FireballAtlas(pub Handle<TextureAtlas>);
-
-// Fireball animation timer
-#[derive(Component)]
-pub struct FireballAnimationTimer(pub Timer);
-
-// Marker component for lives text (suppress warning if unused)
-#[allow(dead_code)]
-#[derive(Component)]
-pub struct LivesText;
-
-#[allow(dead_code)]
-#[derive(Component)]
-pub struct ShipLivesDisplay;
-
-#[derive(Component)]
-pub struct ScoreDisplay;
-
-// Component to track the player's score
-#[derive(Default, Resource)]
-pub struct Score(pub i32);
-
-#[derive(Component)]
-pub struct LaserMovementTimer(pub Timer);
+impl LaserType {
+    // Main color of the laser's sprite, also used to mark what it can damage
+    pub fn color(self) -> Color {
+        match self {
+            LaserType::A => Color::rgb(0.24, 0.73, 0.95),
+            LaserType::B => Color::rgb(0.95, 0.24, 0.64),
+        }
+    }
+}
@@ -0,0 +1,496 @@
+// config.rs
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use crate::difficulty::Difficulty;
+use crate::enemy::EnemyKind;
+
+// Tuning file, inside the assets folder; edits to it are picked up while the game runs
+const CONFIG_PATH: &str = "game.config.ron";
+
+// Plugin for the gameplay tuning values, loaded from `CONFIG_PATH` and reloaded whenever it changes
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .init_resource::<GameConfig>()
+            .init_resource::<GameConfigHandle>()
+            .add_systems(PreUpdate, apply_game_config); // Copy the file's values over once loaded or changed
+    }
+}
+
+// Gameplay tuning values; every section and field may be left out of the file to keep the default below
+#[derive(Resource, Deserialize, Clone, TypeUuid, TypePath)]
+#[uuid = "4b8e1d2c-7a3f-4e59-b6c1-2d9f0e8a7c35"]
+#[serde(default)]
+pub struct GameConfig {
+    pub collision_radius: f32, // Distance at which the ship, boxes, lasers, enemies and the end point touch
+    pub ship: ShipConfig,
+    pub boxes: BoxConfig,
+    pub lasers: LaserConfig,
+    pub enemies: EnemyConfig,
+    pub boss: BossConfig,
+    pub waves: WaveConfig,
+    pub time_attack: TimeAttackConfig,
+    pub extra_lives: ExtraLifeConfig,
+    pub difficulty: DifficultyConfig,
+    pub versus: VersusConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            collision_radius: 30.0,
+            ship: Default::default(),
+            boxes: Default::default(),
+            lasers: Default::default(),
+            enemies: Default::default(),
+            boss: Default::default(),
+            waves: Default::default(),
+            time_attack: Default::default(),
+            extra_lives: Default::default(),
+            difficulty: Default::default(),
+            versus: Default::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    // Checks for values the game can't run with, such as a share written as a percent or a zero
+    // radius; returns the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("collision_radius", self.collision_radius),
+            ("lasers.speed", self.lasers.speed),
+            ("enemies.sight_range", self.enemies.sight_range),
+            ("enemies.lose_range", self.enemies.lose_range),
+            ("enemies.fire_range", self.enemies.fire_range),
+            ("enemies.strafe_distance", self.enemies.strafe_distance), // Divides the strafing speed
+            ("enemies.chaser.fire_interval", self.enemies.chaser.fire_interval),
+            ("enemies.gunner.fire_interval", self.enemies.gunner.fire_interval),
+            ("boss.engage_range", self.boss.engage_range),
+            ("boss.weak_point_radius", self.boss.weak_point_radius),
+            ("waves.interval_decay", self.waves.interval_decay),
+            ("time_attack.checkpoint_radius", self.time_attack.checkpoint_radius),
+            ("versus.round_seconds", self.versus.round_seconds),
+        ];
+        let non_negative = [
+            ("ship.speed", self.ship.speed),
+            ("boxes.speed", self.boxes.speed),
+            ("boxes.spawn_clearance", self.boxes.spawn_clearance),
+            ("lasers.margin", self.lasers.margin),
+            ("enemies.patrol_speed", self.enemies.patrol_speed),
+            ("enemies.chase_speed", self.enemies.chase_speed),
+            ("enemies.strafe_speed", self.enemies.strafe_speed),
+            ("enemies.flee_speed", self.enemies.flee_speed),
+            ("enemies.steering_sharpness", self.enemies.steering_sharpness),
+            ("boss.sweep_distance", self.boss.sweep_distance),
+            ("boss.pursuit_speed", self.boss.pursuit_speed),
+            ("boss.defeat_seconds", self.boss.defeat_seconds), // Timer lengths; negative ones panic
+            ("waves.spawn_margin", self.waves.spawn_margin),
+            ("waves.box_spread", self.waves.box_spread),
+            ("versus.invulnerable_seconds", self.versus.invulnerable_seconds),
+        ];
+        let fractions = [
+            ("enemies.flee_health", self.enemies.flee_health),
+            ("boss.spread_health", self.boss.spread_health),
+            ("boss.ring_health", self.boss.ring_health),
+            ("waves.interval_decay", self.waves.interval_decay),
+            ("waves.gunner_share_per_wave", self.waves.gunner_share_per_wave),
+            ("waves.max_gunner_share", self.waves.max_gunner_share),
+        ];
+
+        if let Some((name, value)) = positive.iter().find(|(_, value)| !(value.is_finite() && *value > 0.0)) {
+            return Err(format!("`{}` must be above 0, not {}", name, value));
+        }
+        if let Some((name, value)) = non_negative.iter().find(|(_, value)| !(value.is_finite() && *value >= 0.0)) {
+            return Err(format!("`{}` can't be negative, not {}", name, value));
+        }
+        if let Some((name, value)) = fractions.iter().find(|(_, value)| !(0.0..=1.0).contains(value)) {
+            return Err(format!("`{}` must be between 0 and 1, not {}", name, value));
+        }
+        if self.enemies.chaser.health == 0 || self.enemies.gunner.health == 0 {
+            return Err("every enemy kind needs a `health` of at least 1".to_string());
+        }
+        if self.extra_lives.thresholds.is_empty() || self.extra_lives.interval <= 0 {
+            return Err("`extra_lives` needs at least one threshold and an `interval` above 0".to_string());
+        }
+        for (name, preset) in [
+            ("easy", &self.difficulty.easy),
+            ("normal", &self.difficulty.normal),
+            ("hard", &self.difficulty.hard),
+        ] {
+            let multipliers = [
+                ("ship_speed", preset.ship_speed),
+                ("box_speed", preset.box_speed),
+                ("laser_speed", preset.laser_speed),
+                ("spawn_rate", preset.spawn_rate), // Divides the wave intervals
+            ];
+            if let Some((field, value)) = multipliers.iter().find(|(_, value)| !(value.is_finite() && *value > 0.0)) {
+                return Err(format!("`difficulty.{}.{}` must be above 0, not {}", name, field, value));
+            }
+            if !(preset.box_count.is_finite() && preset.box_count >= 0.0) {
+                return Err(format!("`difficulty.{}.box_count` can't be negative, not {}", name, preset.box_count));
+            }
+            if preset.starting_lives == 0 {
+                return Err(format!("`difficulty.{}.starting_lives` must be at least 1", name));
+            }
+        }
+        if self.versus.lives == 0 || self.versus.hull == 0 {
+            return Err("`versus.lives` and `versus.hull` must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Speeds are in world units per second, on Normal difficulty
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ShipConfig {
+    pub speed: f32,
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        ShipConfig { speed: 200.0 }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct BoxConfig {
+    pub speed: f32,
+    pub spawn_clearance: f32, // Boxes never start closer than this to the ship
+}
+
+impl Default for BoxConfig {
+    fn default() -> Self {
+        BoxConfig {
+            speed: 100.0,
+            spawn_clearance: 150.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LaserConfig {
+    pub speed: f32,
+    pub margin: f32, // How far past the world edges lasers fly before they are removed
+}
+
+impl Default for LaserConfig {
+    fn default() -> Self {
+        LaserConfig {
+            speed: 500.0,
+            margin: 200.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct EnemyConfig {
+    pub sight_range: f32, // Distances at which enemies notice the ship, give up on it and open fire
+    pub lose_range: f32,
+    pub fire_range: f32,
+    pub patrol_speed: f32, // Top speed in each behavior
+    pub chase_speed: f32,
+    pub strafe_speed: f32,
+    pub flee_speed: f32,
+    pub strafe_distance: f32,    // Distance gunners try to keep from the ship while circling it
+    pub steering_sharpness: f32, // How quickly enemies change velocity; higher is twitchier
+    pub flee_health: f32,        // Enemies flee once their health is down to this share
+    pub score: i32,              // Points for destroying an enemy
+    pub chaser: EnemyKindConfig,
+    pub gunner: EnemyKindConfig,
+}
+
+impl Default for EnemyConfig {
+    fn default() -> Self {
+        EnemyConfig {
+            sight_range: 500.0,
+            lose_range: 800.0,
+            fire_range: 450.0,
+            patrol_speed: 80.0,
+            chase_speed: 150.0,
+            strafe_speed: 130.0,
+            flee_speed: 170.0,
+            strafe_distance: 280.0,
+            steering_sharpness: 3.0,
+            flee_health: 0.5,
+            score: 5,
+            chaser: EnemyKindConfig {
+                health: 2,
+                fire_interval: 1.5,
+            },
+            gunner: EnemyKindConfig {
+                health: 3,
+                fire_interval: 0.9,
+            },
+        }
+    }
+}
+
+impl EnemyConfig {
+    pub fn kind(&self, kind: EnemyKind) -> &EnemyKindConfig {
+        match kind {
+            EnemyKind::Chaser => &self.chaser,
+            EnemyKind::Gunner => &self.gunner,
+        }
+    }
+}
+
+// Stats of one kind of enemy; a kind written in the file needs both fields
+#[derive(Deserialize, Clone)]
+pub struct EnemyKindConfig {
+    pub health: u32,
+    pub fire_interval: f32, // Seconds between shots
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct BossConfig {
+    pub engage_range: f32,   // The boss only attacks while the ship is this close
+    pub sweep_distance: f32, // How far the boss sweeps either side of its position
+    pub pursuit_speed: f32,  // How fast the boss closes in during its last phase
+    pub defeat_seconds: f32, // Length of the defeat sequence
+    pub score: i32,          // Points for defeating the boss
+    pub weak_point_radius: f32,
+    pub spread_health: f32, // Shares of the boss's health at which its spread and ring phases start
+    pub ring_health: f32,
+}
+
+impl Default for BossConfig {
+    fn default() -> Self {
+        BossConfig {
+            engage_range: 700.0,
+            sweep_distance: 200.0,
+            pursuit_speed: 60.0,
+            defeat_seconds: 2.5,
+            score: 50,
+            weak_point_radius: 22.0,
+            spread_health: 0.66,
+            ring_health: 0.33,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WaveConfig {
+    pub spawn_margin: f32,          // How far outside the view new arrivals appear
+    pub interval_decay: f32,        // Each wave comes this much sooner than the one before, down to the level's minimum
+    pub box_spread: f32,            // Most a new box's heading strays from the line to the ship, in radians
+    pub gunner_share_per_wave: f32, // Share of wave enemies that are gunners, growing with each wave up to the maximum
+    pub max_gunner_share: f32,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        WaveConfig {
+            spawn_margin: 60.0,
+            interval_decay: 0.9,
+            box_spread: 0.5,
+            gunner_share_per_wave: 0.1,
+            max_gunner_share: 0.5,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct TimeAttackConfig {
+    pub checkpoint_radius: f32, // How close a ship has to fly to a checkpoint to pass it
+}
+
+impl Default for TimeAttackConfig {
+    fn default() -> Self {
+        TimeAttackConfig { checkpoint_radius: 60.0 }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ExtraLifeConfig {
+    pub thresholds: Vec<i32>, // Scores at which an extra life is awarded
+    pub interval: i32,        // Past the last threshold, another every this many points
+}
+
+impl Default for ExtraLifeConfig {
+    fn default() -> Self {
+        ExtraLifeConfig {
+            thresholds: vec![10, 25, 50],
+            interval: 50,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct DifficultyConfig {
+    pub easy: DifficultyPreset,
+    pub normal: DifficultyPreset,
+    pub hard: DifficultyPreset,
+}
+
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        DifficultyConfig {
+            easy: DifficultyPreset {
+                starting_lives: 7,
+                ship_speed: 1.1,
+                box_speed: 0.75,
+                laser_speed: 1.15,
+                box_count: 0.7,
+                spawn_rate: 0.75,
+            },
+            normal: DifficultyPreset {
+                starting_lives: 5,
+                ship_speed: 1.0,
+                box_speed: 1.0,
+                laser_speed: 1.0,
+                box_count: 1.0,
+                spawn_rate: 1.0,
+            },
+            hard: DifficultyPreset {
+                starting_lives: 3,
+                ship_speed: 0.95,
+                box_speed: 1.3,
+                laser_speed: 0.9,
+                box_count: 1.3,
+                spawn_rate: 1.3,
+            },
+        }
+    }
+}
+
+impl DifficultyConfig {
+    pub fn preset(&self, difficulty: Difficulty) -> &DifficultyPreset {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+        }
+    }
+}
+
+// Lives and multipliers for one difficulty; a preset written in the file needs every field
+#[derive(Deserialize, Clone)]
+pub struct DifficultyPreset {
+    pub starting_lives: u32, // Lives at the start of a run and after using a continue
+    pub ship_speed: f32,
+    pub box_speed: f32, // Boxes and obstacles
+    pub laser_speed: f32,
+    pub box_count: f32,  // Boxes a level starts with
+    pub spawn_rate: f32, // How many boxes and enemies waves bring and how often they come
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct VersusConfig {
+    pub lives: u32,                // Lives each player starts a round with, on any difficulty
+    pub hull: u32,                 // Laser hits a ship takes before it is destroyed
+    pub invulnerable_seconds: f32, // Spawn protection after a ship (re)spawns
+    pub round_seconds: f32,
+    pub score: i32, // Points for destroying the other player's ship
+}
+
+impl Default for VersusConfig {
+    fn default() -> Self {
+        VersusConfig {
+            lives: 3,
+            hull: 3,
+            invulnerable_seconds: 2.0,
+            round_seconds: 120.0,
+            score: 10,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config: GameConfig = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+// Handle keeping the tuning file loaded, so it is watched for changes
+#[derive(Resource)]
+pub struct GameConfigHandle(pub Handle<GameConfig>);
+
+impl FromWorld for GameConfigHandle {
+    fn from_world(world: &mut World) -> Self {
+        GameConfigHandle(world.resource::<AssetServer>().load(CONFIG_PATH))
+    }
+}
+
+// System to copy the tuning file's values into the `GameConfig` resource when it loads and after each edit;
+// a file that fails to parse or holds invalid values leaves the previous values in place
+pub fn apply_game_config(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    handle: Res<GameConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed } if *changed == handle.0 => {
+                if let Some(loaded) = configs.get(&handle.0) {
+                    if let Err(problem) = loaded.validate() {
+                        warn!("Ignoring {}: {}; keeping the previous values", CONFIG_PATH, problem);
+                        continue;
+                    }
+                    *config = loaded.clone();
+                    info!("Applied game config from {}", CONFIG_PATH);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_and_shipped_configs_are_valid() {
+        assert_eq!(GameConfig::default().validate(), Ok(()));
+        let shipped: GameConfig = ron::from_str(include_str!("../assets/game.config.ron")).unwrap();
+        assert_eq!(shipped.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_values_the_game_cant_use() {
+        let mut negative_timer = GameConfig::default();
+        negative_timer.boss.defeat_seconds = -1.0;
+        assert!(negative_timer.validate().unwrap_err().contains("boss.defeat_seconds"));
+
+        let mut percent_share = GameConfig::default();
+        percent_share.enemies.flee_health = 50.0;
+        assert!(percent_share.validate().unwrap_err().contains("enemies.flee_health"));
+
+        let mut no_hull = GameConfig::default();
+        no_hull.versus.hull = 0;
+        assert!(no_hull.validate().unwrap_err().contains("versus.hull"));
+    }
+}
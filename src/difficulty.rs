@@ -0,0 +1,93 @@
+// difficulty.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::component::GameTimer;
+use crate::config::DifficultyConfig;
+use crate::profile::{LifeLost, PlayerProfile};
+use crate::settings::Settings;
+use crate::state::{AppState, GameplaySet};
+
+// Range of the adaptive spawn rate; 1.0 leaves the preset's rate as it is
+const MIN_PRESSURE: f32 = 0.6;
+const MAX_PRESSURE: f32 = 1.6;
+
+// How much each lost life eases off, each kill pushes harder, and each second without dying pushes harder
+const DEATH_RELIEF: f32 = 0.2;
+const KILL_PRESSURE: f32 = 0.02;
+const CALM_PRESSURE_PER_SECOND: f32 = 0.005;
+
+// Plugin for adaptive difficulty, which follows how the player is doing
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AdaptiveDifficulty>()
+            .add_systems(OnEnter(AppState::InGame), reset_adaptive_difficulty) // Every level starts out even
+            .add_systems(Update, adapt_difficulty.in_set(GameplaySet)); // Ease off after deaths, push harder otherwise
+    }
+}
+
+// Difficulty preset, picked in the settings and fixed for a run when it starts; its lives and multipliers are
+// in the game config
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+// How hard adaptive difficulty is currently pushing; only used while it is turned on in the settings
+#[derive(Resource)]
+pub struct AdaptiveDifficulty {
+    pub pressure: f32,
+    kills: Option<u32>, // Kills already counted; taken from the profile on the level's first update
+}
+
+impl Default for AdaptiveDifficulty {
+    fn default() -> Self {
+        AdaptiveDifficulty { pressure: 1.0, kills: None }
+    }
+}
+
+impl AdaptiveDifficulty {
+    // Multiplier on how many boxes and enemies waves bring and how often, for a run at `difficulty`
+    pub fn spawn_rate(&self, difficulty: Difficulty, settings: &Settings, config: &DifficultyConfig) -> f32 {
+        let pressure = if settings.adaptive_difficulty { self.pressure } else { 1.0 };
+        config.preset(difficulty).spawn_rate * pressure
+    }
+}
+
+// System to start adaptive difficulty over at the start of a level
+pub fn reset_adaptive_difficulty(mut adaptive: ResMut<AdaptiveDifficulty>) {
+    *adaptive = AdaptiveDifficulty::default();
+}
+
+// System to ease off after lost lives and push harder while the player keeps scoring kills and staying alive
+pub fn adapt_difficulty(
+    time: Res<Time>,
+    game_timer: Res<GameTimer>,
+    profile: Res<PlayerProfile>,
+    mut life_lost: EventReader<LifeLost>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+) {
+    let deaths = life_lost.iter().count();
+    // Kills go back to zero when a continue starts the run over
+    let kills = profile.kills().saturating_sub(adaptive.kills.unwrap_or(profile.kills()));
+    adaptive.kills = Some(profile.kills());
+
+    let running = game_timer.0.is_some() && !game_timer.1;
+    let calm = if running && deaths == 0 { CALM_PRESSURE_PER_SECOND * time.delta_seconds() } else { 0.0 };
+    let change = kills as f32 * KILL_PRESSURE + calm - deaths as f32 * DEATH_RELIEF;
+    adaptive.pressure = (adaptive.pressure + change).clamp(MIN_PRESSURE, MAX_PRESSURE);
+}
@@ -0,0 +1,146 @@
+// display.rs
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::window::PrimaryWindow;
+use crate::settings::Settings;
+
+// Play area shown at every window size, in world units; the UI is laid out for a window of this size
+pub const LOGICAL_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+// Largest play area the expand mode will show; any more window is covered by bars.
+// Its half-size must stay within the starfield's margin
+const MAX_VIEW_SIZE: Vec2 = Vec2::new(1600.0, 1000.0);
+
+// How far the bars reach past the window edges, so camera shake never uncovers a gap
+const BAR_OVERSCAN: f32 = 64.0;
+
+// Plugin for the camera's fixed logical resolution, scaled to fit the window
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewBounds>()
+            .add_systems(Startup, spawn_camera) // The camera is shared by every level and menu
+            .add_systems(PreUpdate, scale_view); // Fit the play area to the window
+    }
+}
+
+// How the play area fills windows whose shape doesn't match it
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ScaleMode {
+    #[default]
+    Letterbox, // Always show exactly the logical play area, with bars around it
+    Expand,    // Show more of the world along the longer side, up to a limit
+}
+
+impl ScaleMode {
+    pub fn next(self) -> Self {
+        match self {
+            ScaleMode::Letterbox => ScaleMode::Expand,
+            ScaleMode::Expand => ScaleMode::Letterbox,
+        }
+    }
+}
+
+// Half-size of the play area the camera currently shows, in world units
+#[derive(Resource)]
+pub struct ViewBounds {
+    pub half_size: Vec2,
+}
+
+impl Default for ViewBounds {
+    fn default() -> Self {
+        ViewBounds { half_size: LOGICAL_SIZE / 2.0 }
+    }
+}
+
+// Bar covering one side of the window outside the play area
+#[derive(Component, Clone, Copy)]
+pub enum ViewBar {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+// System to spawn the camera along with the bars it carries
+pub fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn((Camera2dBundle::default(), VisibilityBundle::default()))
+        .with_children(|camera| {
+            for bar in [ViewBar::Left, ViewBar::Right, ViewBar::Top, ViewBar::Bottom] {
+                camera.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::BLACK,
+                            custom_size: Some(Vec2::ZERO),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, 900.0), // Over the whole world, within the camera's range
+                        ..Default::default()
+                    },
+                    bar,
+                ));
+            }
+        });
+}
+
+// Play area to show in a window of the given logical size
+fn view_size(mode: ScaleMode, window: Vec2) -> Vec2 {
+    match mode {
+        ScaleMode::Letterbox => LOGICAL_SIZE,
+        ScaleMode::Expand => {
+            let aspect = window.x / window.y;
+            let expanded = if aspect > LOGICAL_SIZE.x / LOGICAL_SIZE.y {
+                Vec2::new(LOGICAL_SIZE.y * aspect, LOGICAL_SIZE.y)
+            } else {
+                Vec2::new(LOGICAL_SIZE.x, LOGICAL_SIZE.x / aspect)
+            };
+            expanded.min(MAX_VIEW_SIZE)
+        }
+    }
+}
+
+// System to scale the camera and UI to the window and cover whatever lies outside the play area
+pub fn scale_view(
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut last: Local<Option<(Vec2, ScaleMode)>>,
+    mut view: ResMut<ViewBounds>,
+    mut ui_scale: ResMut<UiScale>,
+    mut cameras: Query<&mut OrthographicProjection, With<Camera2d>>,
+    mut bars: Query<(&ViewBar, &mut Transform, &mut Sprite)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    if window_size.min_element() <= 0.0 || *last == Some((window_size, settings.scale_mode)) {
+        return;
+    }
+    *last = Some((window_size, settings.scale_mode));
+
+    // World units per window pixel, fitting the whole play area in the window
+    let view_size = view_size(settings.scale_mode, window_size);
+    let units_per_pixel = (view_size / window_size).max_element();
+    for mut projection in cameras.iter_mut() {
+        projection.scaling_mode = ScalingMode::WindowSize(1.0 / units_per_pixel);
+    }
+    view.half_size = view_size / 2.0;
+    ui_scale.scale = (window_size / LOGICAL_SIZE).min_element() as f64;
+
+    let half_view = view_size / 2.0;
+    let half_window = window_size * units_per_pixel / 2.0 + BAR_OVERSCAN;
+    let side = Vec2::new(half_window.x - half_view.x, half_window.y * 2.0);
+    let cap = Vec2::new(half_view.x * 2.0, half_window.y - half_view.y);
+    for (bar, mut transform, mut sprite) in bars.iter_mut() {
+        let (size, position) = match bar {
+            ViewBar::Left => (side, Vec2::new(-(half_view.x + side.x / 2.0), 0.0)),
+            ViewBar::Right => (side, Vec2::new(half_view.x + side.x / 2.0, 0.0)),
+            ViewBar::Top => (cap, Vec2::new(0.0, half_view.y + cap.y / 2.0)),
+            ViewBar::Bottom => (cap, Vec2::new(0.0, -(half_view.y + cap.y / 2.0))),
+        };
+        sprite.custom_size = Some(size);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
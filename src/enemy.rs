@@ -0,0 +1,346 @@
+// enemy.rs
+use std::f32::consts::FRAC_PI_2;
+use std::time::Duration;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::animation::Animations;
+use crate::component::{Faction, GameTimer, Invulnerable, Laser, LaserType, LevelEntity, Ship};
+use crate::config::{EnemyConfig, GameConfig};
+use crate::input::spawn_laser;
+use crate::level::WorldBounds;
+use crate::profile::{LifeLost, PlayerProfile};
+use crate::state::GameplaySet;
+use crate::system::{destroy_ship, laser_hit, nearest_ship, spawn_fireball, LaserImpact};
+
+// Plugin for enemy ships: their behaviors, their fire and what hits them
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                choose_enemy_behaviors, // Pick what each enemy is doing
+                steer_enemies,          // Fly according to the behavior
+                fire_enemy_lasers,      // Shoot at the ship while attacking
+                damage_enemies,         // Player lasers wear enemies down
+                hit_ship,               // Enemy lasers and rams cost a life
+            )
+                .chain()
+                .in_set(GameplaySet),
+        );
+    }
+}
+
+// Kinds of enemy, which set how they attack; their health and fire rate are in the game config
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyKind {
+    Chaser, // Flies straight at the ship
+    Gunner, // Circles the ship at a distance
+}
+
+impl EnemyKind {
+    fn attack(self) -> Behavior {
+        match self {
+            EnemyKind::Chaser => Behavior::Chase,
+            EnemyKind::Gunner => Behavior::Strafe,
+        }
+    }
+}
+
+// An enemy placed in a level file; it patrols between `position` and `position + patrol`
+#[derive(Deserialize, Serialize, Clone)]
+pub struct EnemySpawn {
+    pub kind: EnemyKind,
+    pub position: Vec2,
+    #[serde(default)]
+    pub patrol: Vec2,
+}
+
+// What an enemy is currently doing
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Behavior {
+    Patrol, // Fly back and forth along the patrol route
+    Chase,  // Head straight for the ship
+    Strafe, // Circle the ship at a distance
+    Flee,   // Get away from the ship once damaged
+}
+
+// Enemy ship
+#[derive(Component)]
+pub struct Enemy {
+    pub spawn: EnemySpawn,
+    pub health: u32,
+    max_health: u32, // The kind's health when the enemy spawned
+    pub behavior: Behavior,
+    velocity: Vec2,
+    returning: bool, // Heading back to the start of the patrol route
+    fire_timer: Timer,
+}
+
+impl Enemy {
+    pub fn ai(&self) -> EnemyAi {
+        EnemyAi {
+            behavior: self.behavior,
+            velocity: self.velocity,
+            returning: self.returning,
+            fire_elapsed: self.fire_timer.elapsed_secs(),
+        }
+    }
+
+    fn damaged(&self, config: &EnemyConfig) -> bool {
+        self.health as f32 <= self.max_health as f32 * config.flee_health
+    }
+}
+
+// An enemy's flight and fire state, saved with a run so the enemy carries on where it left off
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct EnemyAi {
+    pub behavior: Behavior,
+    pub velocity: Vec2,
+    pub returning: bool,
+    pub fire_elapsed: f32, // Seconds into the current interval between shots
+}
+
+// Spawns an enemy from the level, at `translation` and with `health` left; a fresh one starts out patrolling
+// unless `ai` carries on a saved one
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &EnemyConfig,
+    spawn: &EnemySpawn,
+    translation: Vec2,
+    health: u32,
+    ai: Option<EnemyAi>,
+) {
+    let kind = config.kind(spawn.kind);
+    let mut enemy = Enemy {
+        spawn: spawn.clone(),
+        health,
+        max_health: kind.health.max(health),
+        behavior: Behavior::Patrol,
+        velocity: Vec2::ZERO,
+        returning: false,
+        fire_timer: Timer::from_seconds(kind.fire_interval, TimerMode::Repeating),
+    };
+    if let Some(ai) = ai {
+        enemy.behavior = ai.behavior;
+        enemy.velocity = ai.velocity;
+        enemy.returning = ai.returning;
+        enemy.fire_timer.set_elapsed(Duration::from_secs_f32(ai.fire_elapsed.max(0.0)));
+    }
+    let color = enemy_color(&enemy);
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("ship.png"),
+            sprite: Sprite {
+                color,
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: translation.extend(0.0),
+                scale: Vec3::new(0.08, 0.08, 1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        enemy,
+        LevelEntity,
+    ));
+}
+
+// Tint by kind, reddening as the enemy is worn down
+fn enemy_color(enemy: &Enemy) -> Color {
+    let base = match enemy.spawn.kind {
+        EnemyKind::Chaser => Vec3::new(1.0, 0.5, 0.3),
+        EnemyKind::Gunner => Vec3::new(0.8, 0.4, 1.0),
+    };
+    let health = enemy.health as f32 / enemy.max_health as f32;
+    let color = Vec3::new(1.0, 0.15, 0.15).lerp(base, health);
+    Color::rgb(color.x, color.y, color.z)
+}
+
+// System to switch each enemy's behavior based on where the nearest ship is and how damaged it is
+pub fn choose_enemy_behaviors(
+    ships: Query<&Transform, With<Ship>>,
+    mut enemies: Query<(&mut Enemy, &Transform), Without<Ship>>,
+    config: Res<GameConfig>,
+) {
+    let config = &config.enemies;
+    for (mut enemy, transform) in enemies.iter_mut() {
+        let ship = nearest_ship(ships.iter(), transform.translation.truncate());
+        let distance = ship.map(|ship| ship.distance(transform.translation.truncate()));
+        enemy.behavior = match (enemy.behavior, distance) {
+            (_, None) => Behavior::Patrol,
+            (_, Some(distance)) if enemy.damaged(config) => {
+                if distance < config.lose_range { Behavior::Flee } else { Behavior::Patrol }
+            }
+            (Behavior::Patrol, Some(distance)) if distance < config.sight_range => enemy.spawn.kind.attack(),
+            (Behavior::Chase | Behavior::Strafe, Some(distance)) if distance > config.lose_range => Behavior::Patrol,
+            (behavior, _) => behavior,
+        };
+    }
+}
+
+// System to fly each enemy according to its behavior, keeping it inside the world
+pub fn steer_enemies(
+    time: Res<Time>,
+    game_timer: Res<GameTimer>,
+    bounds: Res<WorldBounds>,
+    ships: Query<&Transform, With<Ship>>,
+    mut enemies: Query<(&mut Enemy, &mut Transform), Without<Ship>>,
+    config: Res<GameConfig>,
+) {
+    // Enemies freeze with the boxes once the game is over
+    if game_timer.1 {
+        return;
+    }
+    let config = &config.enemies;
+    let delta = time.delta_seconds();
+    for (mut enemy, mut transform) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+        let ship = nearest_ship(ships.iter(), position);
+        let to_ship = ship.map(|ship| ship - position).unwrap_or(Vec2::ZERO);
+        let toward_ship = to_ship.normalize_or_zero();
+
+        let desired = match enemy.behavior {
+            Behavior::Patrol => {
+                let target = if enemy.returning { enemy.spawn.position } else { enemy.spawn.position + enemy.spawn.patrol };
+                if position.distance(target) < 10.0 {
+                    enemy.returning = !enemy.returning;
+                }
+                (target - position).clamp_length_max(config.patrol_speed)
+            }
+            Behavior::Chase => toward_ship * config.chase_speed,
+            Behavior::Strafe => {
+                // Circle the ship while closing in on, or backing off to, the strafing distance
+                let tangent = toward_ship.perp();
+                let closing = ((to_ship.length() - config.strafe_distance) / config.strafe_distance).clamp(-1.0, 1.0);
+                (tangent + toward_ship * closing).normalize_or_zero() * config.strafe_speed
+            }
+            Behavior::Flee => -toward_ship * config.flee_speed,
+        };
+
+        let steering = 1.0 - (-config.steering_sharpness * delta).exp();
+        enemy.velocity = enemy.velocity.lerp(desired, steering);
+        transform.translation = bounds.clamp(transform.translation + enemy.velocity.extend(0.0) * delta);
+
+        // Attacking enemies keep their nose on the ship, the rest face where they are going
+        let facing = match enemy.behavior {
+            Behavior::Chase | Behavior::Strafe => toward_ship,
+            Behavior::Patrol | Behavior::Flee => enemy.velocity.normalize_or_zero(),
+        };
+        if facing != Vec2::ZERO {
+            transform.rotation = Quat::from_rotation_z(facing.y.atan2(facing.x) - FRAC_PI_2);
+        }
+    }
+}
+
+// System to have attacking enemies shoot at the nearest ship when it is in range
+pub fn fire_enemy_lasers(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    ships: Query<&Transform, With<Ship>>,
+    mut enemies: Query<(&mut Enemy, &Transform), Without<Ship>>,
+    config: Res<GameConfig>,
+) {
+    for (mut enemy, transform) in enemies.iter_mut() {
+        let Some(ship) = nearest_ship(ships.iter(), transform.translation.truncate()) else {
+            continue;
+        };
+        let attacking = matches!(enemy.behavior, Behavior::Chase | Behavior::Strafe);
+        let in_range = transform.translation.truncate().distance(ship) < config.enemies.fire_range;
+        if !attacking || !in_range {
+            continue;
+        }
+        if enemy.fire_timer.tick(time.delta()).just_finished() {
+            spawn_laser(
+                &mut commands,
+                &asset_server,
+                transform.translation,
+                transform.rotation,
+                LaserType::B,
+                Faction::Enemy,
+            );
+        }
+    }
+}
+
+// System to damage enemies hit by players' lasers, blowing them up when their health runs out
+pub fn damage_enemies(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    lasers: Query<(Entity, &Transform, &Laser)>,
+    mut enemies: Query<(Entity, &mut Enemy, &Transform, &mut Sprite)>,
+    mut profile: ResMut<PlayerProfile>,
+    mut laser_impact: EventWriter<LaserImpact>,
+    config: Res<GameConfig>,
+) {
+    for (laser_entity, laser_transform, laser) in lasers.iter() {
+        let Faction::Player(player) = laser.faction else {
+            continue;
+        };
+        for (enemy_entity, mut enemy, transform, mut sprite) in enemies.iter_mut() {
+            if enemy.health == 0 || laser_transform.translation.distance(transform.translation) >= config.collision_radius {
+                continue;
+            }
+            laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
+            enemy.health -= 1;
+            sprite.color = enemy_color(&enemy);
+            if enemy.health == 0 {
+                commands.entity(enemy_entity).despawn_recursive();
+                spawn_fireball(&mut commands, &animations, transform.translation);
+                let stats = &mut profile.players[player];
+                stats.score += config.enemies.score;
+                stats.kills += 1;
+            }
+            break;
+        }
+    }
+}
+
+// System to take a life when an enemy laser hits a ship or an enemy rams it
+pub fn hit_ship(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    ships: Query<(Entity, &Ship, &Transform), Without<Invulnerable>>,
+    lasers: Query<(Entity, &Transform, &Laser), Without<Ship>>,
+    enemies: Query<(Entity, &Enemy, &Transform), Without<Ship>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut life_lost: EventWriter<LifeLost>,
+    mut laser_impact: EventWriter<LaserImpact>,
+    config: Res<GameConfig>,
+) {
+    // Lasers and enemies spent on one ship this frame can't hit another
+    let mut spent = Vec::new();
+
+    for (ship_entity, ship, ship_transform) in ships.iter() {
+        let ship_position = ship_transform.translation;
+
+        let shot = lasers.iter().find(|(entity, transform, laser)| {
+            laser.faction == Faction::Enemy
+                && !spent.contains(entity)
+                && transform.translation.distance(ship_position) < config.collision_radius
+        });
+        let rammed = enemies.iter().find(|(entity, enemy, transform)| {
+            enemy.health > 0 && !spent.contains(entity) && transform.translation.distance(ship_position) < config.collision_radius
+        });
+
+        if let Some((laser_entity, laser_transform, _)) = shot {
+            laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
+            spent.push(laser_entity);
+        }
+        // Ramming costs the enemy too
+        if let Some((enemy_entity, _, transform)) = rammed {
+            commands.entity(enemy_entity).despawn_recursive();
+            spawn_fireball(&mut commands, &animations, transform.translation);
+            spent.push(enemy_entity);
+        }
+        if shot.is_some() || rammed.is_some() {
+            spawn_fireball(&mut commands, &animations, ship_position);
+            destroy_ship(&mut commands, &mut profile, &mut life_lost, ship_entity, ship.0);
+        }
+    }
+}
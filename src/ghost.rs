@@ -0,0 +1,191 @@
+// ghost.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::component::{GameTimer, LevelEntity, Ship};
+use crate::level::{CurrentLevel, Levels};
+use crate::profile::PlayerProfile;
+use crate::results::RunOutcome;
+use crate::savegame::RunSnapshot;
+use crate::settings::Settings;
+use crate::state::{AppState, GameMode, GameplaySet};
+use crate::storage;
+use crate::system::setup;
+
+// Ghost files are kept in the data dir, one per level, named after the level
+const GHOST_FILE_PREFIX: &str = "ghost_";
+
+// Tint of the ghost ship; mostly see-through so it never hides anything
+const GHOST_COLOR: Color = Color::rgba(0.6, 0.8, 1.0, 0.35);
+
+// Plugin for recording the ship's path through a level and replaying the fastest one as a ghost
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), start_ghost.after(setup))
+            .add_systems(OnEnter(AppState::Results), save_ghost) // Keep the run if it beat the ghost
+            .add_systems(
+                Update,
+                (
+                    record_ghost, // Sample the ship every tick while the clock runs
+                    play_ghost,   // Move the ghost ship along its recorded path
+                )
+                    .in_set(GameplaySet),
+            );
+    }
+}
+
+// The ship's pose at one tick, `time` seconds after the run's clock started
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct GhostFrame {
+    pub time: f32,
+    pub position: Vec2,
+    pub rotation: Quat,
+}
+
+// A recorded run to the end point
+#[derive(Serialize, Deserialize)]
+pub struct Ghost {
+    pub time: f32, // Completion time of the run
+    pub frames: Vec<GhostFrame>,
+}
+
+impl Ghost {
+    // Where the ship was `time` seconds into the run, between the two closest frames; `None` once the run is over
+    fn pose(&self, time: f32) -> Option<(Vec2, Quat)> {
+        let next = self.frames.partition_point(|frame| frame.time <= time);
+        let after = self.frames.get(next)?;
+        let Some(before) = next.checked_sub(1).map(|index| &self.frames[index]) else {
+            return Some((after.position, after.rotation));
+        };
+        let t = ((time - before.time) / (after.time - before.time)).clamp(0.0, 1.0);
+        Some((before.position.lerp(after.position, t), before.rotation.slerp(after.rotation, t)))
+    }
+}
+
+// The run being recorded and the best run of the level so far
+#[derive(Resource, Default)]
+pub struct GhostRecorder {
+    frames: Option<Vec<GhostFrame>>, // `None` when the run can't make a ghost, e.g. in survival, in co-op or after a continue from a save
+    best: Option<Ghost>,
+}
+
+// The translucent ship replaying the best run
+#[derive(Component)]
+pub struct GhostShip;
+
+// Ghost file of a level, with anything but letters and digits in its name replaced
+fn ghost_file(level_name: &str) -> String {
+    let name: String = level_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    format!("{}{}.ron", GHOST_FILE_PREFIX, name)
+}
+
+// System to start recording a fresh run and, if the ghost is turned on, bring out the level's best one
+pub fn start_ghost(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+    settings: Res<Settings>,
+    profile: Res<PlayerProfile>,
+    saved_run: Option<Res<RunSnapshot>>,
+) {
+    // Survival and versus have nowhere to race to, and a run continued from a save misses its start
+    if matches!(*mode, GameMode::Survival | GameMode::Versus) {
+        commands.insert_resource(GhostRecorder::default());
+        return;
+    }
+    let best: Option<Ghost> = storage::load(&ghost_file(&levels.0[current_level.0].name));
+
+    if let (Some(ghost), true) = (&best, settings.ghost) {
+        let (position, rotation) = ghost.pose(0.0).unwrap_or_default();
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("ship.png"),
+                sprite: Sprite {
+                    color: GHOST_COLOR,
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: position.extend(-0.2),
+                    rotation,
+                    scale: Vec3::new(0.1, 0.1, 1.0), // Same size as the ship
+                },
+                ..Default::default()
+            },
+            GhostShip,
+            LevelEntity,
+        ));
+    }
+
+    // A co-op run has more than one ship to follow, so it can be raced against but doesn't record
+    commands.insert_resource(GhostRecorder {
+        frames: (saved_run.is_none() && !profile.co_op()).then(Vec::new),
+        best,
+    });
+}
+
+// System to record the ship's pose every tick once the clock is running
+pub fn record_ghost(timer: Res<GameTimer>, ships: Query<&Transform, With<Ship>>, mut recorder: ResMut<GhostRecorder>) {
+    let (Some(time), false) = (timer.0, timer.1) else {
+        return;
+    };
+    let (Some(frames), Ok(ship)) = (recorder.frames.as_mut(), ships.get_single()) else {
+        return;
+    };
+    if frames.last().is_some_and(|frame| frame.time >= time) {
+        return;
+    }
+    frames.push(GhostFrame {
+        time,
+        position: ship.translation.truncate(),
+        rotation: ship.rotation,
+    });
+}
+
+// System to move the ghost ship to where the best run was at the current time; it disappears once that run is over
+pub fn play_ghost(
+    timer: Res<GameTimer>,
+    recorder: Res<GhostRecorder>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<GhostShip>>,
+) {
+    let Some(best) = &recorder.best else {
+        return;
+    };
+    let pose = best.pose(timer.0.unwrap_or(0.0));
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        match pose {
+            Some((position, rotation)) => {
+                transform.translation = position.extend(transform.translation.z);
+                transform.rotation = rotation;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// System to save the finished run as the level's ghost when it beat the best one
+pub fn save_ghost(
+    outcome: Res<RunOutcome>,
+    timer: Res<GameTimer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mut recorder: ResMut<GhostRecorder>,
+) {
+    let (RunOutcome::LevelComplete, Some(time)) = (*outcome, timer.0) else {
+        return;
+    };
+    let Some(frames) = recorder.frames.take() else {
+        return;
+    };
+    if recorder.best.as_ref().is_some_and(|best| best.time <= time) || frames.is_empty() {
+        return;
+    }
+    let ghost = Ghost { time, frames };
+    storage::save(&ghost_file(&levels.0[current_level.0].name), &ghost);
+    recorder.best = Some(ghost);
+}
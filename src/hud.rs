@@ -0,0 +1,497 @@
+// hud.rs
+use bevy::prelude::*;
+use crate::boss::{boss_health, Boss, WeakPoint};
+use crate::component::{GameTimer, LaserType, LevelEntity, Ship};
+use crate::input::LaserTypeTracker;
+use crate::level::{CurrentLevel, Levels, Progress};
+use crate::profile::{LifeGained, PlayerProfile};
+use crate::state::{AppState, GameMode};
+use crate::timeattack::{Medal, Splits};
+use crate::ui::FONT_PATH;
+use crate::versus::Round;
+use crate::wave::WaveSpawner;
+
+// Life icons beyond this are summarised by the "x N" counter only
+const MAX_LIFE_ICONS: u32 = 10;
+
+// How long the extra life notice stays up, in seconds
+const EXTRA_LIFE_NOTICE_SECONDS: f32 = 1.5;
+
+// Width of the boss health bar
+const BOSS_BAR_WIDTH: f32 = 400.0;
+
+// Plugin for the in-game heads-up display
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), spawn_hud).add_systems(
+            Update,
+            (
+                update_lives_display,   // Life icons and counters follow each player's lives
+                update_time_display,    // Elapsed time follows the game timer, or the round timer in versus
+                update_split_display,   // Checkpoint splits and the par to beat, in time attack
+                update_score_display,   // Score follows the players' scores
+                update_weapon_display,  // Weapon follows each player's laser type
+                update_level_display,   // Level name follows the current level
+                update_wave_display,    // Countdown to the next wave, and kills in survival
+                update_boss_bar,        // Boss health and phase, while there is a boss
+                show_extra_life_notice, // Announce extra lives
+                fade_extra_life_notice, // Fade the announcement out
+            )
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+// Row holding one ship icon per life of a player
+#[derive(Component)]
+pub struct LivesIcons(pub usize);
+
+// Widgets of the HUD, each bound to one piece of game state
+#[derive(Component)]
+pub struct LivesText(pub usize);
+
+#[derive(Component)]
+pub struct TimeText;
+
+#[derive(Component)]
+pub struct SplitText;
+
+#[derive(Component)]
+pub struct ScoreText;
+
+#[derive(Component)]
+pub struct WeaponText;
+
+#[derive(Component)]
+pub struct LevelText;
+
+#[derive(Component)]
+pub struct WaveText;
+
+// Boss health bar, hidden while there is no boss; the fill's width follows the boss's health
+#[derive(Component)]
+pub struct BossBar;
+
+#[derive(Component)]
+pub struct BossBarFill;
+
+#[derive(Component)]
+pub struct BossBarText;
+
+// Short-lived "Extra life!" notice
+#[derive(Component)]
+pub struct ExtraLifeNotice(pub Timer);
+
+// System to build the HUD, with a row of lives for each player; the widgets fill themselves in on their first update
+pub fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>, profile: Res<PlayerProfile>) {
+    let text_style = TextStyle {
+        font: asset_server.load(FONT_PATH),
+        font_size: 30.0,
+        color: Color::WHITE,
+    };
+    let column = Style {
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(4.0),
+        ..Default::default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            LevelEntity,
+        ))
+        .with_children(|hud| {
+            // Left: lives, time and splits
+            hud.spawn(NodeBundle {
+                style: column.clone(),
+                ..Default::default()
+            })
+            .with_children(|left| {
+                for player in 0..profile.players.len() {
+                    left.spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|lives| {
+                        lives.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    column_gap: Val::Px(4.0),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            LivesIcons(player),
+                        ));
+                        let color = Ship(player).color();
+                        lives.spawn((TextBundle::from_section("", TextStyle { color, ..text_style.clone() }), LivesText(player)));
+                    });
+                }
+                left.spawn((TextBundle::from_section("", text_style.clone()), TimeText));
+                left.spawn((
+                    TextBundle::from_section("", TextStyle { font_size: 24.0, ..text_style.clone() }),
+                    SplitText,
+                ));
+            });
+
+            // Right: score, weapon, level and waves
+            hud.spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::FlexEnd,
+                    ..column
+                },
+                ..Default::default()
+            })
+            .with_children(|right| {
+                right.spawn((TextBundle::from_section("", text_style.clone()), ScoreText));
+                right.spawn((TextBundle::from_section("", text_style.clone()), WeaponText));
+                right.spawn((TextBundle::from_section("", text_style.clone()), LevelText));
+                right.spawn((TextBundle::from_section("", text_style.clone()), WaveText));
+            });
+        });
+
+    // Top centre: the boss's health
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            BossBar,
+            LevelEntity,
+        ))
+        .with_children(|bar| {
+            bar.spawn((TextBundle::from_section("", TextStyle { font_size: 24.0, ..text_style }), BossBarText));
+            bar.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(BOSS_BAR_WIDTH),
+                    height: Val::Px(14.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                border_color: Color::WHITE.into(),
+                ..Default::default()
+            })
+            .with_children(|frame| {
+                frame.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..Default::default()
+                        },
+                        background_color: Color::rgb(0.9, 0.15, 0.15).into(),
+                        ..Default::default()
+                    },
+                    BossBarFill,
+                ));
+            });
+        });
+}
+
+// System to show one ship icon per life, in the player's color, whenever a player's lives change
+pub fn update_lives_display(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    profile: Res<PlayerProfile>,
+    mut shown_lives: Local<Vec<Option<u32>>>,
+    icons_query: Query<(Entity, Ref<LivesIcons>)>,
+    mut text_query: Query<(&LivesText, &mut Text)>,
+) {
+    shown_lives.resize(profile.players.len(), None);
+    for (icons, marker) in icons_query.iter() {
+        let player = marker.0;
+        let Some(lives) = profile.players.get(player).map(|stats| stats.lives) else {
+            continue;
+        };
+        if shown_lives[player] == Some(lives) && !marker.is_added() {
+            continue;
+        }
+        shown_lives[player] = Some(lives);
+
+        let ship_icon = asset_server.load("ship.png");
+        commands.entity(icons).despawn_descendants().with_children(|row| {
+            for _ in 0..lives.min(MAX_LIFE_ICONS) {
+                row.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(24.0),
+                        height: Val::Px(24.0),
+                        ..Default::default()
+                    },
+                    image: UiImage::new(ship_icon.clone()),
+                    background_color: Ship(player).color().into(), // Tints the icon
+                    ..Default::default()
+                });
+            }
+        });
+
+        for (_, mut text) in text_query.iter_mut().filter(|(text, _)| text.0 == player) {
+            text.sections[0].value = if profile.co_op() {
+                format!("P{} x{}", player + 1, lives)
+            } else {
+                format!("x{}", lives)
+            };
+        }
+    }
+}
+
+// System to show the elapsed time whenever the game timer changes; a versus round counts down instead
+pub fn update_time_display(
+    timer: Res<GameTimer>,
+    round: Option<Res<Round>>,
+    mut text_query: Query<(Ref<TimeText>, &mut Text)>,
+) {
+    for (marker, mut text) in text_query.iter_mut() {
+        if let Some(round) = &round {
+            if round.is_changed() || marker.is_added() {
+                text.sections[0].value = format!("Round: {:.0} seconds left", round.0.remaining_secs().ceil());
+            }
+        } else if timer.is_changed() || marker.is_added() {
+            text.sections[0].value = format!("Time: {:.2} seconds", timer.0.unwrap_or(0.0));
+        }
+    }
+}
+
+// System to show the checkpoints passed, the last split against the best run's, and the fastest medal
+// still in reach, while in time attack; redrawn when the splits change or the run falls out of a medal
+pub fn update_split_display(
+    splits: Option<Res<Splits>>,
+    timer: Res<GameTimer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    progress: Res<Progress>,
+    mut shown: Local<(bool, Option<Medal>)>, // Whether splits and which medal were last drawn
+    mut text_query: Query<(Ref<SplitText>, &mut Text)>,
+) {
+    let level = &levels.0[current_level.0];
+    let par = level.time_attack.as_ref().map(|time_attack| time_attack.par);
+    let medal = par.and_then(|par| par.medal(timer.0.unwrap_or(0.0)));
+    let showing = (splits.is_some(), medal);
+    let stale = *shown != showing || splits.as_ref().is_some_and(|splits| splits.is_changed());
+    *shown = showing;
+
+    for (marker, mut text) in text_query.iter_mut() {
+        if !stale && !marker.is_added() {
+            continue;
+        }
+        let mut value = String::new();
+        if let Some(splits) = &splits {
+            if !splits.route_done() {
+                value.push_str(&format!("Checkpoint {}/{}", splits.times.len() + 1, splits.checkpoints));
+            } else if splits.checkpoints > 0 {
+                value.push_str("Goal open");
+            }
+            if let Some(split) = splits.times.last() {
+                value.push_str(&format!("\nSplit: {:.2} s", split));
+                let best = progress.best_run(&level.name).and_then(|run| run.splits.get(splits.times.len() - 1));
+                if let Some(best) = best {
+                    value.push_str(&format!(" ({:+.2})", split - best));
+                }
+            }
+            if let Some(par) = par {
+                match medal {
+                    Some(medal) => value.push_str(&format!("\n{} par: {:.2} s", medal.name(), par.time(medal))),
+                    None => value.push_str("\nPar missed"),
+                }
+            }
+        }
+        text.sections[0].value = value;
+    }
+}
+
+// System to show the score whenever it changes; in co-op, the team's score with each player's share, and
+// in versus just each player's own
+pub fn update_score_display(
+    profile: Res<PlayerProfile>,
+    mode: Res<GameMode>,
+    mut text_query: Query<(Ref<ScoreText>, &mut Text)>,
+) {
+    for (marker, mut text) in text_query.iter_mut() {
+        if profile.is_changed() || marker.is_added() {
+            let shares: Vec<String> = profile
+                .players
+                .iter()
+                .enumerate()
+                .map(|(player, stats)| format!("P{} {}", player + 1, stats.score))
+                .collect();
+            let value = if *mode == GameMode::Versus {
+                format!("Score: {}", shares.join("  "))
+            } else if profile.co_op() {
+                format!("Score: {}\n{}", profile.score(), shares.join("  "))
+            } else {
+                format!("Score: {}", profile.score())
+            };
+            text.sections[0].value = value;
+        }
+    }
+}
+
+// System to show the selected laser whenever it changes, for each player in co-op
+pub fn update_weapon_display(
+    laser_type_tracker: Res<LaserTypeTracker>,
+    profile: Res<PlayerProfile>,
+    mut text_query: Query<(Ref<WeaponText>, &mut Text)>,
+) {
+    let weapon = |player| match laser_type_tracker.current(player) {
+        LaserType::A => "Laser A",
+        LaserType::B => "Laser B",
+    };
+    for (marker, mut text) in text_query.iter_mut() {
+        if laser_type_tracker.is_changed() || marker.is_added() {
+            text.sections[0].value = if profile.co_op() {
+                let weapons: Vec<String> =
+                    (0..profile.players.len()).map(|player| format!("P{} {}", player + 1, weapon(player))).collect();
+                weapons.join("  ")
+            } else {
+                format!("Weapon: {}", weapon(0))
+            };
+        }
+    }
+}
+
+// System to show the level name whenever the current level changes
+pub fn update_level_display(
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mut text_query: Query<(Ref<LevelText>, &mut Text)>,
+) {
+    for (marker, mut text) in text_query.iter_mut() {
+        if current_level.is_changed() || marker.is_added() {
+            text.sections[0].value = levels.0[current_level.0].name.clone();
+        }
+    }
+}
+
+// System to count down to the next wave while the level has waves, and show the kills in survival; the
+// spawner ticks every frame, so it is redrawn only when the whole seconds shown or the kills change
+pub fn update_wave_display(
+    spawner: Option<Res<WaveSpawner>>,
+    profile: Res<PlayerProfile>,
+    mode: Res<GameMode>,
+    mut shown: Local<Option<(u32, f32)>>, // Wave and seconds last drawn
+    mut text_query: Query<(Ref<WaveText>, &mut Text)>,
+) {
+    let countdown = spawner.map(|spawner| (spawner.wave + 1, spawner.until_next.max(0.0).ceil()));
+    let stale = *shown != countdown || (*mode == GameMode::Survival && profile.is_changed());
+    *shown = countdown;
+
+    for (marker, mut text) in text_query.iter_mut() {
+        if !stale && !marker.is_added() {
+            continue;
+        }
+        let mut value =
+            countdown.map(|(wave, seconds)| format!("Wave {} in {:.0} s", wave, seconds)).unwrap_or_default();
+        if *mode == GameMode::Survival {
+            value.push_str(&format!("\nKills: {}", profile.kills()));
+        }
+        text.sections[0].value = value;
+    }
+}
+
+// System to show the boss's health and phase while it is alive
+pub fn update_boss_bar(
+    bosses: Query<(&Boss, &Children)>,
+    weak_points: Query<&WeakPoint>,
+    mut bars: Query<&mut Style, (With<BossBar>, Without<BossBarFill>)>,
+    mut fills: Query<&mut Style, (With<BossBarFill>, Without<BossBar>)>,
+    mut texts: Query<&mut Text, With<BossBarText>>,
+) {
+    let boss = bosses.get_single().ok();
+    for mut style in bars.iter_mut() {
+        let display = if boss.is_some() { Display::Flex } else { Display::None };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+    let Some((boss, children)) = boss else {
+        return;
+    };
+
+    let (health, max_health) = boss_health(weak_points.iter_many(children));
+    for mut style in fills.iter_mut() {
+        style.width = Val::Percent(health as f32 / max_health.max(1) as f32 * 100.0);
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = if boss.defeat.is_some() {
+            "Boss defeated!".to_string()
+        } else {
+            format!("Boss - Phase {}", boss.phase.number())
+        };
+    }
+}
+
+// System to announce an extra life in the middle of the screen, naming the player in co-op
+pub fn show_extra_life_notice(
+    mut commands: Commands,
+    mut life_gained: EventReader<LifeGained>,
+    asset_server: Res<AssetServer>,
+    profile: Res<PlayerProfile>,
+) {
+    if let Some(event) = life_gained.iter().last() {
+        let notice = if profile.co_op() {
+            format!("P{} extra life! ({} lives)", event.player + 1, event.lives)
+        } else {
+            format!("Extra life! ({} lives)", event.lives)
+        };
+        commands.spawn((
+            TextBundle::from_section(
+                notice,
+                TextStyle {
+                    font: asset_server.load(FONT_PATH),
+                    font_size: 40.0,
+                    color: Color::YELLOW,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            })
+            .with_text_alignment(TextAlignment::Center),
+            ExtraLifeNotice(Timer::from_seconds(EXTRA_LIFE_NOTICE_SECONDS, TimerMode::Once)),
+            LevelEntity,
+        ));
+    }
+}
+
+// System to fade out and remove the extra life notice
+pub fn fade_extra_life_notice(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notices: Query<(Entity, &mut ExtraLifeNotice, &mut Text)>,
+) {
+    for (entity, mut notice, mut text) in notices.iter_mut() {
+        notice.0.tick(time.delta());
+        text.sections[0].style.color.set_a(notice.0.percent_left());
+        if notice.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
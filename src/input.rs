@@ -1,71 +1,229 @@
 // input.rs
 use bevy::prelude::*;
-use crate::component::{EndPoint, GameTimer, Laser, LaserMovementTimer, LaserType, Ship, StartPoint};
+use bevy::ecs::system::SystemParam;
+use crate::component::{Faction, GameTimer, Laser, LaserType, LevelEntity, Ship, StartPoint, Thrust};
+use crate::config::GameConfig;
+use crate::display::ViewBounds;
+use crate::level::WorldBounds;
+use crate::profile::{PlayerProfile, MAX_PLAYERS};
 // Replace `Windows` with `Window` in the import statements
 use bevy::window::Window;
 
-// Add a resource to track which laser type to shoot
-#[derive(Resource, Default)]
+// Gap kept between a co-op ship and the edge of the view, so nobody is left behind off-screen
+const LEASH_MARGIN: f32 = 40.0;
+
+// Right stick deflection needed before a gamepad takes over aiming
+const AIM_THRESHOLD: f32 = 0.5;
+
+// Keys flying one player's ship
+pub struct KeyBindings {
+    pub up: &'static [KeyCode],
+    pub down: &'static [KeyCode],
+    pub left: &'static [KeyCode],
+    pub right: &'static [KeyCode],
+    pub fire: &'static [KeyCode],
+    pub switch: &'static [KeyCode], // Switch laser type
+}
+
+// Playing alone, either set of keys flies the ship
+const SOLO_KEYS: KeyBindings = KeyBindings {
+    up: &[KeyCode::Up, KeyCode::W],
+    down: &[KeyCode::Down, KeyCode::S],
+    left: &[KeyCode::Left, KeyCode::A],
+    right: &[KeyCode::Right, KeyCode::D],
+    fire: &[KeyCode::Space],
+    switch: &[KeyCode::Q],
+};
+
+// In co-op the first player keeps WASD, Space, Q and the mouse, and the second player takes the
+// arrow keys with the right-hand Ctrl and Shift (or Numpad 0 and the numpad's Enter)
+const CO_OP_KEYS: [KeyBindings; MAX_PLAYERS] = [
+    KeyBindings {
+        up: &[KeyCode::W],
+        down: &[KeyCode::S],
+        left: &[KeyCode::A],
+        right: &[KeyCode::D],
+        fire: &[KeyCode::Space],
+        switch: &[KeyCode::Q],
+    },
+    KeyBindings {
+        up: &[KeyCode::Up],
+        down: &[KeyCode::Down],
+        left: &[KeyCode::Left],
+        right: &[KeyCode::Right],
+        fire: &[KeyCode::ControlRight, KeyCode::Numpad0],
+        switch: &[KeyCode::ShiftRight, KeyCode::NumpadEnter],
+    },
+];
+
+// Keyboard and gamepad controls of every player; the first connected gamepad flies the first ship,
+// the second gamepad the second ship, and so on
+#[derive(SystemParam)]
+pub struct PlayerControls<'w> {
+    keyboard: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    profile: Res<'w, PlayerProfile>,
+}
+
+impl PlayerControls<'_> {
+    fn keys(&self, player: usize) -> &'static KeyBindings {
+        if self.profile.co_op() {
+            &CO_OP_KEYS[player % MAX_PLAYERS]
+        } else {
+            &SOLO_KEYS
+        }
+    }
+
+    fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        let mut gamepads: Vec<Gamepad> = self.gamepads.iter().collect();
+        gamepads.sort_by_key(|gamepad| gamepad.id);
+        gamepads.get(player).copied()
+    }
+
+    fn button_pressed(&self, player: usize, button_type: GamepadButtonType) -> bool {
+        self.gamepad(player)
+            .is_some_and(|gamepad| self.gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    fn button_just_pressed(&self, player: usize, button_type: GamepadButtonType) -> bool {
+        self.gamepad(player)
+            .is_some_and(|gamepad| self.gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    fn stick(&self, player: usize, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let Some(gamepad) = self.gamepad(player) else {
+            return Vec2::ZERO;
+        };
+        let axis = |axis_type| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.0);
+        Vec2::new(axis(x), axis(y))
+    }
+
+    // Direction the player is steering in, at most one unit long
+    pub fn direction(&self, player: usize) -> Vec2 {
+        let keys = self.keys(player);
+        let held = |keys: &[KeyCode], button| self.keyboard.any_pressed(keys.iter().copied()) || self.button_pressed(player, button);
+        let mut direction = Vec2::ZERO;
+        if held(keys.up, GamepadButtonType::DPadUp) {
+            direction.y += 1.0;
+        }
+        if held(keys.down, GamepadButtonType::DPadDown) {
+            direction.y -= 1.0;
+        }
+        if held(keys.left, GamepadButtonType::DPadLeft) {
+            direction.x -= 1.0;
+        }
+        if held(keys.right, GamepadButtonType::DPadRight) {
+            direction.x += 1.0;
+        }
+        let stick = self.stick(player, GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+        (direction + stick).clamp_length_max(1.0)
+    }
+
+    pub fn fire(&self, player: usize) -> bool {
+        self.keyboard.any_just_pressed(self.keys(player).fire.iter().copied())
+            || self.button_just_pressed(player, GamepadButtonType::South)
+    }
+
+    pub fn switch(&self, player: usize) -> bool {
+        self.keyboard.any_just_pressed(self.keys(player).switch.iter().copied())
+            || self.button_just_pressed(player, GamepadButtonType::West)
+    }
+
+    // Direction the right stick is pushed in, once it is pushed far enough to aim with
+    pub fn aim(&self, player: usize) -> Option<Vec2> {
+        let stick = self.stick(player, GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+        (stick.length() > AIM_THRESHOLD).then_some(stick)
+    }
+}
+
+// Add a resource to track which laser type each player shoots
+#[derive(Resource)]
 pub struct LaserTypeTracker {
-    pub shoot_a: bool, // True if `laser_a_01.png` is to be shot, False for `laser_b_01.png`
+    pub shoot_a: [bool; MAX_PLAYERS], // By player: true if `laser_a_01.png` is to be shot, False for `laser_b_01.png`
+}
+
+impl Default for LaserTypeTracker {
+    fn default() -> Self {
+        LaserTypeTracker { shoot_a: [true; MAX_PLAYERS] }
+    }
+}
+
+impl LaserTypeTracker {
+    pub fn current(&self, player: usize) -> LaserType {
+        if self.shoot_a[player % MAX_PLAYERS] {
+            LaserType::A
+        } else {
+            LaserType::B
+        }
+    }
+}
+
+// Event fired when the player shoots a laser (not when one is restored from a save)
+#[derive(Event)]
+pub struct LaserFired {
+    pub laser_type: LaserType,
+}
+
+// Rotation that points a ship the way the mouse aims it, toward `direction`
+fn facing(direction: Vec2) -> Quat {
+    Quat::from_rotation_z(direction.y.atan2(direction.x))
 }
 
 // 1. **Ship Movement and Rotation:**
 pub fn ship_movement(
-    keyboard_input: Res<Input<KeyCode>>,
+    controls: PlayerControls,
     mut param_set: ParamSet<(
-        Query<&mut Transform, With<Ship>>,
+        Query<(&Ship, &mut Transform, &mut Thrust)>,
         Query<&Transform, With<StartPoint>>,
     )>,
+    cameras: Query<&Transform, (With<Camera2d>, Without<Ship>)>,
     time: Res<Time>,
-    windows: Query<&Window>,
+    bounds: Res<WorldBounds>,
+    view: Res<ViewBounds>,
     mut timer: ResMut<GameTimer>,
+    config: Res<GameConfig>,
 ) {
-    if let Ok(mut transform) = param_set.p0().get_single_mut() {
-        let mut direction = Vec3::ZERO;
-
-        // Start the game timer when the player moves
-        if timer.0.is_none() && (keyboard_input.pressed(KeyCode::Up) || keyboard_input.pressed(KeyCode::Down)
-            || keyboard_input.pressed(KeyCode::Left) || keyboard_input.pressed(KeyCode::Right)
-            || keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::S)
-            || keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::D)) {
-            timer.0 = Some(0.0);
-        }
+    let speed = config.ship.speed * config.difficulty.preset(controls.profile.difficulty).ship_speed;
+    let camera = cameras.get_single().map(|camera| camera.translation.truncate()).ok();
 
-        // Arrow keys or WASD movement
-        if keyboard_input.pressed(KeyCode::Up) || keyboard_input.pressed(KeyCode::W) {
-            direction.y += 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::Down) || keyboard_input.pressed(KeyCode::S) {
-            direction.y -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::Left) || keyboard_input.pressed(KeyCode::A) {
-            direction.x -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::Right) || keyboard_input.pressed(KeyCode::D) {
-            direction.x += 1.0;
+    for (ship, mut transform, mut thrust) in param_set.p0().iter_mut() {
+        // Arrow keys, WASD, the d-pad or the left stick
+        let direction = controls.direction(ship.0);
+
+        // Start the game timer when any player moves
+        if timer.0.is_none() && direction != Vec2::ZERO {
+            timer.0 = Some(0.0);
         }
 
-        let speed = 200.0;
-        transform.translation += direction.normalize_or_zero() * speed * time.delta_seconds();
+        thrust.0 = direction.extend(0.0);
+        transform.translation += thrust.0 * speed * time.delta_seconds();
 
-        if let Ok(window) = windows.get_single() {
-            let half_width = window.width() / 2.0;
-            let half_height = window.height() / 2.0;
+        // Keep the ship inside the level's world and, in co-op, on screen
+        transform.translation = bounds.clamp(transform.translation);
+        if let (true, Some(camera)) = (controls.profile.co_op(), camera) {
+            let reach = (view.half_size - LEASH_MARGIN).max(Vec2::ZERO);
+            let position = transform.translation.truncate().clamp(camera - reach, camera + reach);
+            transform.translation = position.extend(transform.translation.z);
+        }
 
-            transform.translation.x = transform.translation.x.clamp(-half_width, half_width);
-            transform.translation.y = transform.translation.y.clamp(-half_height, half_height);
+        // The mouse aims the first ship; the others aim with the right stick or face where they fly
+        if ship.0 > 0 {
+            if let Some(aim) = controls.aim(ship.0).or((direction != Vec2::ZERO).then_some(direction)) {
+                transform.rotation = facing(aim);
+            }
         }
     }
 }
 
 // 2. **Rotate Ship Based on Mouse Click:**
 pub fn rotate_ship_on_click(
-    mut ship_query: Query<&mut Transform, With<Ship>>,
+    mut ship_query: Query<(&Ship, &mut Transform)>,
     mouse_button_input: Res<Input<MouseButton>>,
 ) {
-    if let Ok(mut transform) = ship_query.get_single_mut() {
-        if mouse_button_input.just_pressed(MouseButton::Left) {
+    for (ship, mut transform) in ship_query.iter_mut() {
+        if ship.0 == 0 && mouse_button_input.just_pressed(MouseButton::Left) {
             // Rotate 90 degrees clockwise
             let current_rotation = transform.rotation.to_euler(EulerRot::XYZ).2;
             let new_rotation = current_rotation + std::f32::consts::FRAC_PI_2;
@@ -78,102 +236,93 @@ pub fn rotate_ship_on_click(
 
 pub fn shoot_laser(
     mut commands: Commands,
-    keyboard_input: Res<Input<KeyCode>>,
-    ship_query: Query<&Transform, With<Ship>>,  // Query to get the ship's transform
-    asset_server: Res<AssetServer>,             // Asset server to load textures
+    controls: PlayerControls,
+    ship_query: Query<(&Ship, &Transform)>,    // Query to get each ship's transform
+    asset_server: Res<AssetServer>,            // Asset server to load textures
+    laser_type_tracker: Res<LaserTypeTracker>, // Which laser type each player has selected
+    mut laser_fired: EventWriter<LaserFired>,  // Lets audio and effects react to the shot
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        // Get the ship's position and rotation
-        if let Ok(ship_transform) = ship_query.get_single() {
-            // Load the appropriate laser texture
-            let laser_texture = asset_server.load("laser_a_01.png");
-
-            // Spawn the laser entity with all necessary components
-            commands.spawn((
-                SpriteBundle {
-                    texture: laser_texture,  // Texture for the laser
-                    transform: Transform {
-                        translation: ship_transform.translation,  // Start at the ship's position
-                        rotation: ship_transform.rotation,        // Maintain ship's rotation
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                Laser {
-                    laser_type: LaserType::A,  // Initialize with laser type A
-                },
-                LaserMovementTimer(Timer::from_seconds(0.05, TimerMode::Repeating)), // Timer for movement updates
-            ));
+    for (ship, ship_transform) in ship_query.iter() {
+        if controls.fire(ship.0) {
+            // Fire from the ship's position along its rotation
+            let laser_type = laser_type_tracker.current(ship.0);
+            spawn_laser(
+                &mut commands,
+                &asset_server,
+                ship_transform.translation,
+                ship_transform.rotation,
+                laser_type,
+                Faction::Player(ship.0),
+            );
+            laser_fired.send(LaserFired { laser_type });
         }
     }
 }
-pub fn move_laser(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut laser_query: Query<(Entity, &mut Transform, &mut LaserMovementTimer), With<Laser>>, // Use &mut LaserMovementTimer
-) {
-    let laser_speed = 500.0;
-
-    for (entity, mut transform, mut timer) in laser_query.iter_mut() {
-        if timer.0.tick(time.delta()).finished() {
-            let direction = transform.rotation * Vec3::Y;
-            transform.translation += direction * laser_speed * time.delta_seconds();
 
-            if transform.translation.y > 800.0 || transform.translation.y < -800.0 ||
-               transform.translation.x > 1200.0 || transform.translation.x < -1200.0 {
-                commands.entity(entity).despawn();
-            }
+// Switch between laser types with Q, or the player's own switch key in co-op
+pub fn switch_laser_type(
+    controls: PlayerControls,
+    mut laser_type_tracker: ResMut<LaserTypeTracker>,
+) {
+    for player in 0..controls.profile.players.len() {
+        if controls.switch(player) {
+            laser_type_tracker.shoot_a[player] = !laser_type_tracker.shoot_a[player];
         }
     }
 }
 
-// 4. **Check End Point Reached - Game Ends:**
-pub fn check_end_point_reached(
-    mut commands: Commands,
-    mut query: Query<(Entity, &Transform), With<Ship>>,
-    end_point_query: Query<&Transform, With<EndPoint>>,
-    mut timer: ResMut<GameTimer>,
-    asset_server: Res<AssetServer>,
+// Spawns a laser of the given type travelling along `rotation`; lasers take their player's tint, and enemy lasers are red
+pub fn spawn_laser(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    translation: Vec3,
+    rotation: Quat,
+    laser_type: LaserType,
+    faction: Faction,
 ) {
-    if let Ok((ship_entity, ship_transform)) = query.get_single_mut() {
-        if let Ok(end_point_transform) = end_point_query.get_single() {
-            let collision_distance = 30.0;
-            if ship_transform.translation.distance(end_point_transform.translation) < collision_distance {
-                println!("Ship reached the end point!");
-                commands.entity(ship_entity).despawn(); // Remove the starship
-                timer.1 = true;
-
-                // Display "Game Over" message
-                commands.spawn(TextBundle {
-                    text: Text::from_section(
-                        "Game Over!",
-                        TextStyle {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 50.0,
-                            color: Color::GREEN,
-                        }
-                    ),
-                    style: Style {
-                        position_type: PositionType::Absolute,
-                        top: Val::Px(250.0),
-                        left: Val::Px(200.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                });
-            }
-        }
-    }
+    // Load the appropriate laser texture
+    let laser_texture = match laser_type {
+        LaserType::A => asset_server.load("laser_a_01.png"),
+        LaserType::B => asset_server.load("laser_b_01.png"),
+    };
+
+    // Spawn the laser entity with all necessary components
+    commands.spawn((
+        SpriteBundle {
+            texture: laser_texture,  // Texture for the laser
+            sprite: Sprite {
+                color: match faction {
+                    Faction::Player(player) => Ship(player).color(),
+                    Faction::Enemy => Color::rgb(1.0, 0.35, 0.35),
+                },
+                ..Default::default()
+            },
+            transform: Transform {
+                translation,  // Start at the ship's position
+                rotation,     // Maintain ship's rotation
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Laser { laser_type, faction },
+        LevelEntity,
+    ));
 }
 
-// 5. **Rotate Ship to Follow Cursor:**
+// 4. **Rotate Ship to Follow Cursor:**
 pub fn rotate_ship_follow_cursor(
     windows: Query<&Window>,
-    mut ship_query: Query<&mut Transform, With<Ship>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut ship_query: Query<(&Ship, &mut Transform)>,
 ) {
-    if let Ok(window) = windows.get_single() {
-        if let Some(cursor_position) = window.cursor_position() {
-            if let Ok(mut transform) = ship_query.get_single_mut() {
+    if let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) {
+        // The cursor is in window coordinates; the camera maps it into the (scrolling) world
+        let cursor_position = window
+            .cursor_position()
+            .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+        if let Some(cursor_position) = cursor_position {
+            // Only the first player aims with the mouse
+            for (_, mut transform) in ship_query.iter_mut().filter(|(ship, _)| ship.0 == 0) {
                 let direction = cursor_position - transform.translation.truncate();
                 let angle = direction.y.atan2(direction.x);
                 transform.rotation = Quat::from_rotation_z(angle);
@@ -0,0 +1,338 @@
+// level.rs
+use bevy::asset::FileAssetIo;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::boss::BossSpawn;
+use crate::enemy::EnemySpawn;
+use crate::obstacle::ObstacleSpawn;
+use crate::timeattack::{BestRun, TimeAttackDef};
+use crate::wave::WaveDef;
+
+// Folder of the level files, inside the assets folder; levels are played in file name order
+const LEVELS_DIR: &str = "levels";
+const LEVEL_EXTENSION: &str = ".level.ron";
+
+// Definition of a playable level, read from a level file
+#[derive(Deserialize)]
+pub struct LevelDef {
+    pub name: String,
+    pub bounds: Vec2,     // Size of the world, centred on the origin
+    pub start: Vec3,      // Where the ship spawns and respawns
+    pub end: Vec3,        // Where the ship has to get to
+    pub box_count: usize, // Number of boxes spawned at the start of the level
+    #[serde(default)]
+    pub seed: Option<u64>, // Seed for the level's background; derived from the name if left out
+    #[serde(default = "default_star_density")]
+    pub star_density: f32, // Background stars per 100x100 area, over all layers
+    #[serde(default)]
+    pub enemies: Vec<EnemySpawn>, // Enemy ships and their patrol routes
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleSpawn>, // Boxes placed by hand, with their movement behaviors
+    #[serde(default)]
+    pub boss: Option<BossSpawn>, // Boss guarding the end point, which opens once it is defeated
+    #[serde(default)]
+    pub waves: Option<WaveDef>, // Waves of boxes and enemies sent in over time; survival always has them
+    #[serde(default)]
+    pub time_attack: Option<TimeAttackDef>, // Par times and checkpoints for time attack
+}
+
+fn default_star_density() -> f32 {
+    0.6
+}
+
+impl LevelDef {
+    // Seed for everything generated for the level's looks
+    pub fn seed(&self) -> u64 {
+        // FNV-1a of the name, so a level keeps its look without setting a seed
+        self.seed.unwrap_or_else(|| {
+            self.name
+                .bytes()
+                .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+        })
+    }
+
+    // Checks for values the game can't run with; returns the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.waves {
+            Some(waves) => waves.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+// Level files may leave out the inner brackets of behaviors, e.g. `Homing(turn_rate: 1.0)`,
+// and the `Some(...)` around optional values
+fn level_options() -> ron::Options {
+    ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::UNWRAP_VARIANT_NEWTYPES)
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+// All levels, in the order they are unlocked
+#[derive(Resource)]
+pub struct Levels(pub Vec<LevelDef>);
+
+impl Levels {
+    // Reads every level file; unreadable files are skipped with a warning
+    pub fn load() -> Self {
+        let dir = FileAssetIo::get_base_path().join("assets").join(LEVELS_DIR);
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap_or_else(|error| panic!("can't read levels from {}: {}", dir.display(), error))
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.to_string_lossy().ends_with(LEVEL_EXTENSION))
+            .collect();
+        paths.sort();
+
+        let levels: Vec<LevelDef> = paths
+            .iter()
+            .filter_map(|path| {
+                let level = fs::read_to_string(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|contents| level_options().from_str(&contents).map_err(|error| error.to_string()))
+                    .and_then(|level: LevelDef| level.validate().map(|()| level));
+                if let Err(error) = &level {
+                    warn!("Skipping level {}: {}", path.display(), error);
+                }
+                level.ok()
+            })
+            .collect();
+        assert!(!levels.is_empty(), "no levels found in {}", dir.display());
+        Levels(levels)
+    }
+}
+
+// Playable area of the current level, centred on the origin
+#[derive(Resource)]
+pub struct WorldBounds {
+    pub half_size: Vec2,
+}
+
+impl WorldBounds {
+    pub fn new(size: Vec2) -> Self {
+        WorldBounds { half_size: size / 2.0 }
+    }
+
+    // Moves a position inside the world
+    pub fn clamp(&self, position: Vec3) -> Vec3 {
+        position.truncate().clamp(-self.half_size, self.half_size).extend(position.z)
+    }
+
+    // True if `position` is inside the world grown by `margin` on every side
+    pub fn contains(&self, position: Vec3, margin: f32) -> bool {
+        let limit = self.half_size + margin;
+        position.x.abs() <= limit.x && position.y.abs() <= limit.y
+    }
+}
+
+// Index into `Levels` of the level being played
+#[derive(Resource, Default)]
+pub struct CurrentLevel(pub usize);
+
+// Number of entries kept in each per-level leaderboard
+pub const MAX_RECORDS: usize = 5;
+
+// Save file for `Progress`, inside the data dir
+pub const PROGRESS_FILE: &str = "progress.ron";
+
+// A leaderboard entry for a final score
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScoreRecord {
+    pub initials: String,
+    pub score: i32,
+}
+
+// A leaderboard entry for a completion time in seconds
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TimeRecord {
+    pub initials: String,
+    pub time: f32,
+}
+
+// A survival leaderboard entry: how long the player lasted and how much they destroyed
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SurvivalRecord {
+    pub initials: String,
+    pub time: f32,
+    pub kills: u32,
+}
+
+impl SurvivalRecord {
+    // True if a run of `time` and `kills` ranks above this one; longer survival wins, with kills breaking ties
+    fn beaten_by(&self, time: f32, kills: u32) -> bool {
+        time > self.time || (time == self.time && kills > self.kills)
+    }
+}
+
+// Top scores (highest first) and best times (fastest first) of one level, plus its longest survival
+// runs when played as an arena and its fastest time attack run
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct LevelRecords {
+    pub scores: Vec<ScoreRecord>,
+    pub times: Vec<TimeRecord>,
+    #[serde(default)]
+    pub survival: Vec<SurvivalRecord>,
+    #[serde(default)]
+    pub time_attack: Option<BestRun>,
+}
+
+// Which levels the player has unlocked and their leaderboards, persisted in `PROGRESS_FILE`
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    pub unlocked: usize,                        // Number of unlocked levels, counted from the first one
+    pub initials: String,                       // Last initials entered, offered again next time
+    pub records: BTreeMap<String, LevelRecords>, // Leaderboards by level name
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress {
+            unlocked: 1,
+            initials: "AAA".to_string(),
+            records: BTreeMap::new(),
+        }
+    }
+}
+
+impl Progress {
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level < self.unlocked.max(1)
+    }
+
+    // Unlocks the level following `level`, if there is one
+    pub fn unlock_after(&mut self, level: usize, level_count: usize) {
+        self.unlocked = self.unlocked.max((level + 2).min(level_count));
+    }
+
+    pub fn records(&self, level_name: &str) -> Option<&LevelRecords> {
+        self.records.get(level_name)
+    }
+
+    pub fn best_time(&self, level_name: &str) -> Option<f32> {
+        self.records(level_name)?.times.first().map(|record| record.time)
+    }
+
+    pub fn best_survival(&self, level_name: &str) -> Option<f32> {
+        self.records(level_name)?.survival.first().map(|record| record.time)
+    }
+
+    pub fn best_run(&self, level_name: &str) -> Option<&BestRun> {
+        self.records(level_name)?.time_attack.as_ref()
+    }
+
+    // True if `score` would make it onto the level's leaderboard
+    pub fn score_qualifies(&self, level_name: &str, score: i32) -> bool {
+        let scores = self.records(level_name).map(|records| records.scores.as_slice()).unwrap_or_default();
+        score > 0 && (scores.len() < MAX_RECORDS || scores.iter().any(|record| score > record.score))
+    }
+
+    // True if `time` would make it onto the level's best times
+    pub fn time_qualifies(&self, level_name: &str, time: f32) -> bool {
+        let times = self.records(level_name).map(|records| records.times.as_slice()).unwrap_or_default();
+        times.len() < MAX_RECORDS || times.iter().any(|record| time < record.time)
+    }
+
+    // True if a survival run would make it onto the level's survival leaderboard
+    pub fn survival_qualifies(&self, level_name: &str, time: f32, kills: u32) -> bool {
+        let survival = self.records(level_name).map(|records| records.survival.as_slice()).unwrap_or_default();
+        time > 0.0 && (survival.len() < MAX_RECORDS || survival.iter().any(|record| record.beaten_by(time, kills)))
+    }
+
+    pub fn add_score(&mut self, level_name: &str, initials: &str, score: i32) {
+        let scores = &mut self.records.entry(level_name.to_string()).or_default().scores;
+        let position = scores.iter().position(|record| score > record.score).unwrap_or(scores.len());
+        scores.insert(position, ScoreRecord { initials: initials.to_string(), score });
+        scores.truncate(MAX_RECORDS);
+    }
+
+    pub fn add_time(&mut self, level_name: &str, initials: &str, time: f32) {
+        let times = &mut self.records.entry(level_name.to_string()).or_default().times;
+        let position = times.iter().position(|record| time < record.time).unwrap_or(times.len());
+        times.insert(position, TimeRecord { initials: initials.to_string(), time });
+        times.truncate(MAX_RECORDS);
+    }
+
+    pub fn add_survival(&mut self, level_name: &str, initials: &str, time: f32, kills: u32) {
+        let survival = &mut self.records.entry(level_name.to_string()).or_default().survival;
+        let position = survival.iter().position(|record| record.beaten_by(time, kills)).unwrap_or(survival.len());
+        survival.insert(position, SurvivalRecord { initials: initials.to_string(), time, kills });
+        survival.truncate(MAX_RECORDS);
+    }
+
+    // Keeps `run` as the level's best time attack run if it is the fastest yet
+    pub fn record_best_run(&mut self, level_name: &str, run: BestRun) {
+        let best = &mut self.records.entry(level_name.to_string()).or_default().time_attack;
+        if best.as_ref().map_or(true, |best| run.time < best.time) {
+            *best = Some(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_qualify_until_the_board_is_full_then_only_above_the_lowest() {
+        let mut progress = Progress::default();
+        assert!(!progress.score_qualifies("Alpha", 0));
+        for score in [50, 40, 30, 20, 10] {
+            assert!(progress.score_qualifies("Alpha", score));
+            progress.add_score("Alpha", "AAA", score);
+        }
+        assert!(!progress.score_qualifies("Alpha", 10));
+        assert!(progress.score_qualifies("Alpha", 11));
+    }
+
+    #[test]
+    fn scores_insert_in_order_and_truncate_to_max_records() {
+        let mut progress = Progress::default();
+        for score in [10, 30, 20, 50, 40, 35] {
+            progress.add_score("Alpha", "AAA", score);
+        }
+        let scores: Vec<i32> = progress.records("Alpha").unwrap().scores.iter().map(|record| record.score).collect();
+        assert_eq!(scores, vec![50, 40, 35, 30, 20]);
+    }
+
+    #[test]
+    fn times_keep_the_fastest_first() {
+        let mut progress = Progress::default();
+        for time in [30.0, 10.0, 20.0, 50.0, 40.0, 60.0] {
+            progress.add_time("Alpha", "AAA", time);
+        }
+        let times: Vec<f32> = progress.records("Alpha").unwrap().times.iter().map(|record| record.time).collect();
+        assert_eq!(times, vec![10.0, 20.0, 30.0, 40.0, 50.0]);
+        assert_eq!(progress.best_time("Alpha"), Some(10.0));
+        assert!(!progress.time_qualifies("Alpha", 50.0));
+        assert!(progress.time_qualifies("Alpha", 49.0));
+    }
+
+    #[test]
+    fn survival_ties_are_broken_by_kills() {
+        let mut progress = Progress::default();
+        progress.add_survival("Arena", "AAA", 60.0, 5);
+        progress.add_survival("Arena", "BBB", 60.0, 8);
+        progress.add_survival("Arena", "CCC", 60.0, 5);
+        let initials: Vec<&str> =
+            progress.records("Arena").unwrap().survival.iter().map(|record| record.initials.as_str()).collect();
+        assert_eq!(initials, vec!["BBB", "AAA", "CCC"]);
+        assert!(!progress.survival_qualifies("Arena", 0.0, 10));
+    }
+
+    #[test]
+    fn levels_with_a_zero_wave_interval_are_rejected() {
+        let level = |waves: &str| -> LevelDef {
+            let source = format!(
+                "(name: \"Arena\", bounds: (800, 600), start: (0, 0, 0), end: (0, 200, 0), box_count: 0, waves: {})",
+                waves
+            );
+            level_options().from_str(&source).unwrap()
+        };
+        assert_eq!(level("(interval: 10.0)").validate(), Ok(()));
+        assert!(level("(interval: 0.0)").validate().unwrap_err().contains("waves.interval"));
+        assert!(level("(min_interval: 0.0)").validate().unwrap_err().contains("waves.min_interval"));
+    }
+}
@@ -1,44 +1,129 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)] // Common for Bevy systems and queries
 
+use std::time::Duration;
+use bevy::asset::ChangeWatcher;
 use bevy::prelude::*;
 use system::{
-    animate_fireball, box_movement, box_ship_collision, check_end_point_reached,
-    detect_laser_collision, detect_starship_box_collision, move_laser, setup, update_timer_display,
+    BoxDestroyed, LaserImpact, box_movement, check_end_point_reached, cleanup_level,
+    detect_laser_collision, detect_starship_box_collision, move_laser, respawn_or_game_over, setup,
+    tick_game_timer, update_thrusters,
 };
-use input::{LaserTypeTracker, ship_movement, rotate_ship_follow_cursor, shoot_laser, rotate_ship_on_click};
+use input::{
+    LaserFired, LaserTypeTracker, ship_movement, rotate_ship_follow_cursor, shoot_laser, rotate_ship_on_click, switch_laser_type,
+};
+use state::{AppState, GameplaySet, PauseState, finish_restart};
 
+mod animation;
+mod audio;
+mod boss;
+mod camera;
 mod component;
+mod config;
+mod difficulty;
+mod display;
+mod enemy;
+mod ghost;
 mod system;
 mod input;
+mod hud;
+mod level;
+mod menu;
+mod minimap;
+mod obstacle;
+mod particle;
+mod pause;
+mod profile;
+mod results;
+mod savegame;
+mod settings;
+mod starfield;
+mod state;
+mod storage;
+mod timeattack;
+mod ui;
+mod versus;
+mod wave;
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins) // Adds Bevy's default plugins
+        // Adds Bevy's default plugins, watching the assets folder so edited files are reloaded
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            watch_for_changes: ChangeWatcher::with_delay(Duration::from_millis(200)),
+            ..Default::default()
+        }))
+        .add_plugins((
+            config::ConfigPlugin,             // Gameplay tuning file, reloaded while the game runs
+            display::DisplayPlugin,           // Fixed logical resolution scaled to the window
+            ui::UiPlugin,                     // Shared menu widgets and navigation
+            settings::SettingsPlugin,         // Player settings and the settings menu
+            pause::PausePlugin,               // Pause menu overlay
+            menu::MenuPlugin,                 // Title screen and level select
+            results::ResultsPlugin,           // Game-over/level-complete screens and leaderboards
+            savegame::SaveGamePlugin,         // Save a run mid-level and continue it later
+            hud::HudPlugin,                   // Lives, time, score, weapon and level display
+        ))
+        .add_plugins((
+            profile::ProfilePlugin,           // Lives, score, continues and extra lives
+            difficulty::DifficultyPlugin,     // Adaptive difficulty following recent deaths and kills
+            audio::GameAudioPlugin,           // Music, engine and sound effects
+            animation::SpriteAnimationPlugin, // Sprite-sheet animations defined in asset files
+            particle::ParticlePlugin,         // Exhaust, debris and spark particles
+            camera::GameCameraPlugin,         // Follow camera, screen shake, hit-stop and screen flash
+            starfield::StarfieldPlugin,       // Parallax starfield background
+            minimap::MinimapPlugin,           // Minimap and off-screen indicators
+            enemy::EnemyPlugin,               // Enemy ships, their behaviors and their fire
+            obstacle::ObstaclePlugin,         // Homing, weaving, orbiting and waypoint obstacles
+            boss::BossPlugin,                 // Level bosses with phases and weak points
+            wave::WavePlugin,                 // Waves of boxes and enemies from the screen edges
+            timeattack::TimeAttackPlugin,     // Time attack checkpoints, splits and medals
+            ghost::GhostPlugin,               // Record runs and replay the fastest as a ghost
+        ))
+        .add_plugins(versus::VersusPlugin) // Versus rounds where players' lasers hit each other
+
+        // Game states
+        .add_state::<AppState>()
+        .add_state::<PauseState>()
+        .configure_set(
+            Update,
+            GameplaySet
+                .run_if(in_state(AppState::InGame))
+                .run_if(in_state(PauseState::Running)),
+        )
 
         // Insert resources
         .insert_resource(LaserTypeTracker::default()) // Track which laser type to shoot
+        .add_event::<LaserFired>() // Shots fired, for audio and effects
+        .add_event::<BoxDestroyed>() // Boxes destroyed, for audio and effects
+        .add_event::<LaserImpact>() // Laser hits, for effects
         .insert_resource(component::GameTimer(None, false)) // Game timer resource
-        .insert_resource(component::ShipLives(5)) // Initialize with 5 ship lives
+        .insert_resource(level::Levels::load()) // All playable levels, from the level files
+        .init_resource::<level::CurrentLevel>() // Level picked from the menus
+        .init_resource::<state::GameMode>() // Campaign, survival, time attack or versus, picked from the title screen
+        .insert_resource(storage::load::<level::Progress>(level::PROGRESS_FILE).unwrap_or_default()) // Unlocked levels and leaderboards
 
-        // Use the recommended way to add startup systems
-        .add_startup_system(setup) // Setup the initial game state
+        .add_systems(OnEnter(AppState::InGame), setup) // Setup the level
+        .add_systems(OnExit(AppState::InGame), cleanup_level) // Tear the level down
+        .add_systems(OnEnter(AppState::Restarting), finish_restart) // Go straight back in game
 
         // Add update systems using the new syntax
         .add_systems(
             Update,
             (
                 box_movement,                  // Handles box movement in the game
-                box_ship_collision,            // Detects and processes collisions between the ship and boxes
                 ship_movement,                 // Handles ship movement based on user input
                 rotate_ship_follow_cursor,     // Rotates the ship to follow the mouse cursor
                 rotate_ship_on_click,          // Rotates the ship based on mouse click (added to avoid dead code)
                 shoot_laser,                   // Handles shooting lasers from the ship
+                switch_laser_type,             // Switches between laser types
                 move_laser,                    // Moves the laser in its direction
                 detect_laser_collision,        // Detects laser and box collisions
-                update_timer_display,          // Updates the game timer display
+                tick_game_timer,               // Advances the game timer
                 detect_starship_box_collision, // Detects collisions between the starship and boxes
+                respawn_or_game_over,          // Respawns the ship or ends the game after a lost life
                 check_end_point_reached,       // Checks if the ship has reached the end point
-                animate_fireball,              // Animates the fireball on collision
-            ),
+                update_thrusters,              // Shows the engine flame while the ship moves
+            )
+                .in_set(GameplaySet),
         )
 
         // Start the app
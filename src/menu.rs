@@ -0,0 +1,340 @@
+// menu.rs
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use crate::config::GameConfig;
+use crate::level::{CurrentLevel, Levels, Progress};
+use crate::profile::{PlayerProfile, MAX_PLAYERS};
+use crate::savegame::{discard_saved_run, has_saved_run, is_saved_mode, load_saved_run};
+use crate::settings::{Settings, SettingsMenuState};
+use crate::state::{AppState, GameMode};
+use crate::ui::{spawn_button, spawn_menu_root, spawn_title, ButtonActivated, MenuInput, FONT_PATH};
+
+// Plugin for the title screen and the level select screen
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<NewRunPromptState>()
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(AppState::MainMenu), despawn_menu::<MainMenu>)
+            .add_systems(OnEnter(AppState::LevelSelect), spawn_level_select)
+            .add_systems(OnExit(AppState::LevelSelect), despawn_menu::<LevelSelectMenu>)
+            // Hide the title screen while the settings menu is on top of it
+            .add_systems(OnEnter(SettingsMenuState::Open), despawn_menu::<MainMenu>)
+            // Bring it back when the settings menu closes; not on `OnEnter(Closed)`, which also runs at startup
+            .add_systems(
+                OnTransition {
+                    from: SettingsMenuState::Open,
+                    to: SettingsMenuState::Closed,
+                },
+                spawn_main_menu.run_if(in_state(AppState::MainMenu)),
+            )
+            // The new run prompt stands in for the screen the run was started from
+            .add_systems(
+                OnEnter(NewRunPromptState::Open),
+                (despawn_menu::<MainMenu>, despawn_menu::<LevelSelectMenu>, spawn_new_run_prompt),
+            )
+            .add_systems(OnExit(NewRunPromptState::Open), despawn_menu::<NewRunPrompt>)
+            .add_systems(
+                OnTransition {
+                    from: NewRunPromptState::Open,
+                    to: NewRunPromptState::Closed,
+                },
+                (
+                    spawn_main_menu.run_if(in_state(AppState::MainMenu)),
+                    spawn_level_select.run_if(in_state(AppState::LevelSelect)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_main_menu_buttons
+                        .run_if(in_state(AppState::MainMenu))
+                        .run_if(in_state(SettingsMenuState::Closed))
+                        .run_if(in_state(NewRunPromptState::Closed)), // Play, Level Select, Survival, Time Attack, Versus, Players, Settings and Quit
+                    handle_level_select_buttons
+                        .run_if(in_state(AppState::LevelSelect))
+                        .run_if(in_state(NewRunPromptState::Closed)), // Start an unlocked level
+                    handle_new_run_prompt_buttons.run_if(in_state(NewRunPromptState::Open)), // Start the new run or go back
+                ),
+            );
+    }
+}
+
+// Root node of the title screen
+#[derive(Component)]
+pub struct MainMenu;
+
+// Root node of the level select screen
+#[derive(Component)]
+pub struct LevelSelectMenu;
+
+// Root node of the prompt asking before a new run replaces the saved one
+#[derive(Component)]
+pub struct NewRunPrompt;
+
+// Whether the new run prompt is shown, in place of the title or level select screen
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum NewRunPromptState {
+    #[default]
+    Closed,
+    Open,
+}
+
+// Buttons of the title screen
+#[derive(Component, Clone, Copy)]
+pub enum MainMenuButton {
+    Continue,
+    Play,
+    LevelSelect,
+    Survival,
+    TimeAttack,
+    Versus,
+    Players, // Switches between playing alone and local co-op
+    Settings,
+    Quit,
+}
+
+// Buttons of the level select screen
+#[derive(Component, Clone, Copy)]
+pub enum LevelSelectButton {
+    Level(usize),
+    Back,
+}
+
+// Buttons of the new run prompt
+#[derive(Component, Clone, Copy)]
+pub enum NewRunPromptButton {
+    Start,
+    Cancel,
+}
+
+fn players_label(players: usize) -> String {
+    format!("Players: {}", players)
+}
+
+// System to build the title screen
+pub fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    let font = asset_server.load(FONT_PATH);
+    let root = spawn_menu_root(&mut commands, Color::rgb(0.02, 0.02, 0.08));
+    commands.entity(root).insert(MainMenu).with_children(|parent| {
+        spawn_title(parent, &font, "Starship");
+        if has_saved_run() {
+            spawn_button(parent, &font, "Continue", 0, MainMenuButton::Continue);
+        }
+        spawn_button(parent, &font, "Play", 1, MainMenuButton::Play);
+        spawn_button(parent, &font, "Level Select", 2, MainMenuButton::LevelSelect);
+        spawn_button(parent, &font, "Survival", 3, MainMenuButton::Survival);
+        spawn_button(parent, &font, "Time Attack", 4, MainMenuButton::TimeAttack);
+        spawn_button(parent, &font, "Versus", 5, MainMenuButton::Versus);
+        spawn_button(parent, &font, &players_label(settings.players), 6, MainMenuButton::Players);
+        spawn_button(parent, &font, "Settings", 7, MainMenuButton::Settings);
+        spawn_button(parent, &font, "Quit", 8, MainMenuButton::Quit);
+    });
+}
+
+// System to build the level select screen from the level list and the player's progress; in survival and
+// versus, unlocked levels serve as arenas, and in time attack they show the best run and its medal
+pub fn spawn_level_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    progress: Res<Progress>,
+    mode: Res<GameMode>,
+) {
+    let font = asset_server.load(FONT_PATH);
+    let root = spawn_menu_root(&mut commands, Color::rgb(0.02, 0.02, 0.08));
+    commands.entity(root).insert(LevelSelectMenu).with_children(|parent| {
+        let title = match *mode {
+            GameMode::Campaign => "Level Select",
+            GameMode::Survival => "Survival - Pick an Arena",
+            GameMode::TimeAttack => "Time Attack - Pick a Level",
+            GameMode::Versus => "Versus - Pick an Arena",
+        };
+        spawn_title(parent, &font, title);
+        for (index, level) in levels.0.iter().enumerate() {
+            let best = match *mode {
+                GameMode::Campaign => progress.best_time(&level.name),
+                GameMode::Survival => progress.best_survival(&level.name),
+                GameMode::TimeAttack => progress.best_run(&level.name).map(|run| run.time),
+                GameMode::Versus => None,
+            };
+            let medal = match *mode {
+                GameMode::TimeAttack => progress.best_run(&level.name).and_then(|run| run.medal),
+                _ => None,
+            };
+            let label = if !progress.is_unlocked(index) {
+                format!("{} - Locked", level.name)
+            } else if let Some(best) = best {
+                let medal = medal.map(|medal| format!(" ({})", medal.name())).unwrap_or_default();
+                format!("{} - Best: {:.2} s{}", level.name, best, medal)
+            } else {
+                level.name.clone()
+            };
+            spawn_button(parent, &font, &label, index, LevelSelectButton::Level(index));
+        }
+        spawn_button(parent, &font, "Back", levels.0.len(), LevelSelectButton::Back);
+    });
+}
+
+// System to build the prompt asking before a new run replaces the saved one
+pub fn spawn_new_run_prompt(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(FONT_PATH);
+    let root = spawn_menu_root(&mut commands, Color::rgb(0.02, 0.02, 0.08));
+    commands.entity(root).insert(NewRunPrompt).with_children(|parent| {
+        spawn_title(parent, &font, "Replace the Saved Run?");
+        spawn_button(parent, &font, "Start New Run", 0, NewRunPromptButton::Start);
+        spawn_button(parent, &font, "Cancel", 1, NewRunPromptButton::Cancel);
+    });
+}
+
+// Starts the run set up in the current level, mode and profile; a run that would be saved when left first asks
+// before it replaces the saved one
+fn start_run(
+    mode: GameMode,
+    next_app_state: &mut NextState<AppState>,
+    next_prompt_state: &mut NextState<NewRunPromptState>,
+) {
+    if is_saved_mode(mode) && has_saved_run() {
+        next_prompt_state.set(NewRunPromptState::Open);
+    } else {
+        next_app_state.set(AppState::InGame);
+    }
+}
+
+// System to remove a menu screen identified by the marker on its root node
+pub fn despawn_menu<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// System to act on title screen button presses
+pub fn handle_main_menu_buttons(
+    mut commands: Commands,
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<(&MainMenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    levels: Res<Levels>,
+    progress: Res<Progress>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut profile: ResMut<PlayerProfile>,
+    mut settings: ResMut<Settings>,
+    mut mode: ResMut<GameMode>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_settings_state: ResMut<NextState<SettingsMenuState>>,
+    mut next_prompt_state: ResMut<NextState<NewRunPromptState>>,
+    mut exit: EventWriter<AppExit>,
+    config: Res<GameConfig>,
+) {
+    for event in activated.iter() {
+        if let Ok((button, children)) = buttons.get(event.0) {
+            match button {
+                MainMenuButton::Continue => match load_saved_run() {
+                    Some(snapshot) if snapshot.level < levels.0.len() => {
+                        current_level.0 = snapshot.level;
+                        *mode = snapshot.mode;
+                        *profile = snapshot.profile.clone(); // The HUD lays out a row per player of the saved run
+                        commands.insert_resource(snapshot);
+                        next_app_state.set(AppState::InGame);
+                    }
+                    _ => warn!("The saved run could not be loaded"),
+                },
+                MainMenuButton::Play => {
+                    // Continue from the furthest level the player has unlocked
+                    current_level.0 = progress.unlocked.clamp(1, levels.0.len()) - 1;
+                    *profile = PlayerProfile::new(settings.difficulty, settings.players, &config.difficulty);
+                    *mode = GameMode::Campaign;
+                    start_run(*mode, &mut next_app_state, &mut next_prompt_state);
+                }
+                MainMenuButton::LevelSelect => {
+                    *mode = GameMode::Campaign;
+                    next_app_state.set(AppState::LevelSelect);
+                }
+                MainMenuButton::Survival => {
+                    *mode = GameMode::Survival;
+                    next_app_state.set(AppState::LevelSelect);
+                }
+                MainMenuButton::TimeAttack => {
+                    *mode = GameMode::TimeAttack;
+                    next_app_state.set(AppState::LevelSelect);
+                }
+                MainMenuButton::Versus => {
+                    *mode = GameMode::Versus;
+                    next_app_state.set(AppState::LevelSelect);
+                }
+                MainMenuButton::Players => {
+                    settings.players = settings.players % MAX_PLAYERS + 1;
+                    let mut labels = texts.iter_many_mut(children);
+                    while let Some(mut text) = labels.fetch_next() {
+                        text.sections[0].value = players_label(settings.players);
+                    }
+                }
+                MainMenuButton::Settings => next_settings_state.set(SettingsMenuState::Open),
+                MainMenuButton::Quit => exit.send(AppExit),
+            }
+        }
+    }
+}
+
+// System to act on level select button presses
+pub fn handle_level_select_buttons(
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<&LevelSelectButton>,
+    menu_input: MenuInput,
+    progress: Res<Progress>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut profile: ResMut<PlayerProfile>,
+    settings: Res<Settings>,
+    mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_prompt_state: ResMut<NextState<NewRunPromptState>>,
+    config: Res<GameConfig>,
+) {
+    if menu_input.back() {
+        next_state.set(AppState::MainMenu);
+    }
+
+    for event in activated.iter() {
+        if let Ok(button) = buttons.get(event.0) {
+            match button {
+                LevelSelectButton::Level(index) if progress.is_unlocked(*index) => {
+                    current_level.0 = *index;
+                    // Versus always takes every player, whatever the title screen is set to
+                    let players = if *mode == GameMode::Versus { MAX_PLAYERS } else { settings.players };
+                    *profile = PlayerProfile::new(settings.difficulty, players, &config.difficulty);
+                    start_run(*mode, &mut next_state, &mut next_prompt_state);
+                }
+                LevelSelectButton::Level(_) => {} // Locked levels can't be started
+                LevelSelectButton::Back => next_state.set(AppState::MainMenu),
+            }
+        }
+    }
+}
+
+// System to act on the new run prompt: drop the saved run and start, or go back to the screen the run was
+// started from
+pub fn handle_new_run_prompt_buttons(
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<&NewRunPromptButton>,
+    menu_input: MenuInput,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_prompt_state: ResMut<NextState<NewRunPromptState>>,
+) {
+    if menu_input.back() {
+        next_prompt_state.set(NewRunPromptState::Closed);
+    }
+
+    for event in activated.iter() {
+        if let Ok(button) = buttons.get(event.0) {
+            match button {
+                NewRunPromptButton::Start => {
+                    discard_saved_run();
+                    next_prompt_state.set(NewRunPromptState::Closed);
+                    next_app_state.set(AppState::InGame);
+                }
+                NewRunPromptButton::Cancel => next_prompt_state.set(NewRunPromptState::Closed),
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// minimap.rs
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use crate::display::ViewBounds;
+use crate::component::{BoxDirection, BoxEntity, EndPoint, LevelEntity, Ship};
+use crate::enemy::{Behavior, Enemy};
+use crate::level::{CurrentLevel, Levels, WorldBounds};
+use crate::state::AppState;
+use crate::system::setup;
+
+// Longest side of the minimap, in pixels
+const MINIMAP_SIZE: f32 = 200.0;
+
+// Off-screen threats get an arrow once they are this close to the edge of the view and heading into it,
+// or attacking in the case of enemies
+const THREAT_RANGE: f32 = 400.0;
+const MAX_THREAT_INDICATORS: usize = 8;
+
+// Gap between the arrows and the edge of the view
+const INDICATOR_INSET: f32 = 24.0;
+
+const GOAL_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const THREAT_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+
+// Plugin for the corner minimap and the arrows pointing at things outside the view
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), spawn_minimap.after(setup))
+            .add_systems(
+                Update,
+                (
+                    add_minimap_markers,    // Every ship, goal, box and enemy gets a marker
+                    update_minimap_markers, // Markers follow their entities and go with them
+                    update_minimap_view,    // Outline of what the camera sees
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            // Placed against the camera once it has settled for the frame
+            .add_systems(
+                PostUpdate,
+                update_offscreen_indicators
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+// Minimap panel; its size matches the world's proportions
+#[derive(Component)]
+pub struct Minimap;
+
+// Outline on the minimap of the area the camera shows
+#[derive(Component)]
+pub struct MinimapView;
+
+// Dot on the minimap standing for `target`
+#[derive(Component)]
+pub struct MinimapMarker {
+    pub target: Entity,
+}
+
+// Arrow at the edge of the view pointing at something outside it
+#[derive(Component)]
+pub struct OffscreenIndicator;
+
+// System to build the minimap for the current level's world
+pub fn spawn_minimap(mut commands: Commands, levels: Res<Levels>, current_level: Res<CurrentLevel>) {
+    let bounds = levels.0[current_level.0].bounds;
+    let size = bounds * MINIMAP_SIZE / bounds.max_element();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    width: Val::Px(size.x),
+                    height: Val::Px(size.y),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                border_color: Color::rgba(0.4, 0.6, 1.0, 0.6).into(),
+                ..Default::default()
+            },
+            Minimap,
+            LevelEntity,
+        ))
+        .with_children(|minimap| {
+            minimap.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..Default::default()
+                    },
+                    border_color: Color::rgba(1.0, 1.0, 1.0, 0.4).into(),
+                    ..Default::default()
+                },
+                MinimapView,
+            ));
+        });
+}
+
+// Where a world position falls on the minimap, in percent from the top-left corner
+fn minimap_percent(bounds: &WorldBounds, position: Vec2) -> Vec2 {
+    let relative = (position + bounds.half_size) / (bounds.half_size * 2.0);
+    Vec2::new(relative.x, 1.0 - relative.y).clamp(Vec2::ZERO, Vec2::ONE) * 100.0
+}
+
+// System to add a marker for every new ship, goal, box and enemy
+pub fn add_minimap_markers(
+    mut commands: Commands,
+    minimaps: Query<Entity, With<Minimap>>,
+    ships: Query<Entity, Added<Ship>>,
+    end_points: Query<Entity, Added<EndPoint>>,
+    boxes: Query<Entity, Added<BoxEntity>>,
+    enemies: Query<Entity, Added<Enemy>>,
+) {
+    let Ok(minimap) = minimaps.get_single() else {
+        return;
+    };
+    let kinds = [
+        (ships.iter().collect::<Vec<_>>(), Color::rgb(0.4, 1.0, 1.0), 6.0),
+        (end_points.iter().collect(), GOAL_COLOR, 8.0),
+        (boxes.iter().collect(), Color::rgb(0.8, 0.6, 0.3), 4.0),
+        (enemies.iter().collect(), Color::rgb(1.0, 0.2, 0.6), 6.0),
+    ];
+
+    commands.entity(minimap).with_children(|minimap| {
+        for (targets, color, size) in kinds {
+            for target in targets {
+                minimap.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(size),
+                            height: Val::Px(size),
+                            margin: UiRect::new(Val::Px(-size / 2.0), Val::Auto, Val::Px(-size / 2.0), Val::Auto),
+                            ..Default::default()
+                        },
+                        background_color: color.into(),
+                        ..Default::default()
+                    },
+                    MinimapMarker { target },
+                ));
+            }
+        }
+    });
+}
+
+// System to move markers to their entities and remove the ones whose entity is gone
+pub fn update_minimap_markers(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+    targets: Query<&Transform>,
+    mut markers: Query<(Entity, &MinimapMarker, &mut Style)>,
+) {
+    for (entity, marker, mut style) in markers.iter_mut() {
+        match targets.get(marker.target) {
+            Ok(transform) => {
+                let percent = minimap_percent(&bounds, transform.translation.truncate());
+                style.left = Val::Percent(percent.x);
+                style.top = Val::Percent(percent.y);
+            }
+            Err(_) => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+// System to outline the camera's view on the minimap
+pub fn update_minimap_view(
+    bounds: Res<WorldBounds>,
+    view: Res<ViewBounds>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    mut views: Query<&mut Style, With<MinimapView>>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let half_view = view.half_size;
+    let center = camera.translation.truncate();
+    let top_left = minimap_percent(&bounds, center + Vec2::new(-half_view.x, half_view.y));
+    let bottom_right = minimap_percent(&bounds, center + Vec2::new(half_view.x, -half_view.y));
+
+    for mut style in views.iter_mut() {
+        style.left = Val::Percent(top_left.x);
+        style.top = Val::Percent(top_left.y);
+        style.width = Val::Percent(bottom_right.x - top_left.x);
+        style.height = Val::Percent(bottom_right.y - top_left.y);
+    }
+}
+
+// System to point arrows from the edge of the view at the goal, at boxes about to fly in and at attacking enemies
+pub fn update_offscreen_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    view: Res<ViewBounds>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    end_points: Query<&Transform, With<EndPoint>>,
+    boxes: Query<(&Transform, &BoxDirection), With<BoxEntity>>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut indicators: Query<
+        (Entity, &mut Transform, &mut Sprite, &mut Visibility),
+        (With<OffscreenIndicator>, Without<Camera2d>, Without<EndPoint>, Without<BoxEntity>, Without<Enemy>),
+    >,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let center = camera.translation.truncate();
+    let half_view = view.half_size;
+    let outside_view = |position: Vec2| {
+        let offset = (position - center).abs();
+        offset.x > half_view.x || offset.y > half_view.y
+    };
+
+    // The goal, then the nearest boxes heading into view and enemies on the attack
+    let mut targets: Vec<(Vec2, Color)> = end_points
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .filter(|position| outside_view(*position))
+        .map(|position| (position, GOAL_COLOR))
+        .collect();
+
+    let incoming_boxes = boxes
+        .iter()
+        .map(|(transform, direction)| (transform.translation.truncate(), direction.0.truncate()))
+        .filter(|(position, direction)| direction.dot(center - *position) > 0.0)
+        .map(|(position, _)| position);
+    let attacking_enemies = enemies
+        .iter()
+        .filter(|(_, enemy)| matches!(enemy.behavior, Behavior::Chase | Behavior::Strafe))
+        .map(|(transform, _)| transform.translation.truncate());
+    let mut threats: Vec<(f32, Vec2)> = incoming_boxes
+        .chain(attacking_enemies)
+        .filter(|position| outside_view(*position))
+        .filter_map(|position| {
+            let beyond_edge = ((position - center).abs() - half_view).max_element();
+            (beyond_edge < THREAT_RANGE).then_some((beyond_edge, position))
+        })
+        .collect();
+    threats.sort_by(|a, b| a.0.total_cmp(&b.0));
+    targets.extend(threats.into_iter().take(MAX_THREAT_INDICATORS).map(|(_, position)| (position, THREAT_COLOR)));
+
+    // Reuse the existing arrows, adding more when needed and hiding the spare ones
+    let mut spare = indicators.iter_mut();
+    let inset_half_view = (half_view - INDICATOR_INSET).max(Vec2::ONE);
+    for (position, color) in targets {
+        let direction = position - center;
+        let reach = (inset_half_view / direction.abs().max(Vec2::splat(f32::EPSILON))).min_element();
+        let transform = Transform::from_translation((center + direction * reach).extend(50.0))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)));
+
+        match spare.next() {
+            Some((_, mut indicator_transform, mut sprite, mut visibility)) => {
+                *indicator_transform = transform;
+                sprite.color = color;
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                commands.spawn((
+                    SpriteBundle {
+                        texture: asset_server.load("indicator_arrow.png"),
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(24.0)),
+                            ..Default::default()
+                        },
+                        transform,
+                        ..Default::default()
+                    },
+                    OffscreenIndicator,
+                    LevelEntity,
+                ));
+            }
+        }
+    }
+    for (_, _, _, mut visibility) in spare {
+        *visibility = Visibility::Hidden;
+    }
+}
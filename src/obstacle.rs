@@ -0,0 +1,193 @@
+// obstacle.rs
+use std::f32::consts::TAU;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::component::{BoxDirection, BoxEntity, GameTimer, Ship};
+use crate::state::GameplaySet;
+use crate::system::{box_movement, nearest_ship};
+
+// Distance at which a waypoint counts as reached
+const WAYPOINT_REACHED: f32 = 10.0;
+
+// Plugin for obstacle movement behaviors; each steers the box's `BoxDirection`, which `box_movement` then follows
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (
+                    home_obstacles,    // Turn toward the ship
+                    orbit_obstacles,   // Circle around a point
+                    follow_waypoints,  // Fly from waypoint to waypoint
+                )
+                    .before(box_movement),
+                weave_obstacles.after(box_movement), // Sway across the direction of travel
+            )
+                .in_set(GameplaySet),
+        );
+    }
+}
+
+// Turns toward the ship, by at most `turn_rate` radians per second
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
+// Sways from side to side across the direction of travel
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SineWave {
+    pub amplitude: f32, // Furthest distance either side of the straight path
+    pub frequency: f32, // Sways per second
+    #[serde(default)]
+    pub elapsed: f32,
+}
+
+// Circles `center` at `radius`, anticlockwise unless `clockwise`
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Orbit {
+    pub center: Vec2,
+    pub radius: f32,
+    #[serde(default)]
+    pub clockwise: bool,
+}
+
+// Flies to each point in turn, starting over after the last
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Waypoints {
+    pub points: Vec<Vec2>,
+    #[serde(default)]
+    pub next: usize,
+}
+
+// A movement behavior as written in level files; several can be combined on one obstacle
+#[derive(Serialize, Deserialize, Clone)]
+pub enum ObstacleBehavior {
+    Homing(Homing),
+    SineWave(SineWave),
+    Orbit(Orbit),
+    Waypoints(Waypoints),
+}
+
+impl ObstacleBehavior {
+    // Adds the behavior's component to an obstacle
+    pub fn insert(&self, obstacle: &mut EntityCommands) {
+        match self {
+            ObstacleBehavior::Homing(homing) => obstacle.insert(homing.clone()),
+            ObstacleBehavior::SineWave(sine_wave) => obstacle.insert(sine_wave.clone()),
+            ObstacleBehavior::Orbit(orbit) => obstacle.insert(orbit.clone()),
+            ObstacleBehavior::Waypoints(waypoints) => obstacle.insert(waypoints.clone()),
+        };
+    }
+}
+
+// The behavior components an obstacle may have
+pub type ObstacleComponents = (
+    Option<&'static Homing>,
+    Option<&'static SineWave>,
+    Option<&'static Orbit>,
+    Option<&'static Waypoints>,
+);
+
+// Reads an obstacle's behaviors back from its components, e.g. to save them
+pub fn obstacle_behaviors(
+    (homing, sine_wave, orbit, waypoints): (Option<&Homing>, Option<&SineWave>, Option<&Orbit>, Option<&Waypoints>),
+) -> Vec<ObstacleBehavior> {
+    let mut behaviors = Vec::new();
+    if let Some(homing) = homing {
+        behaviors.push(ObstacleBehavior::Homing(homing.clone()));
+    }
+    if let Some(sine_wave) = sine_wave {
+        behaviors.push(ObstacleBehavior::SineWave(sine_wave.clone()));
+    }
+    if let Some(orbit) = orbit {
+        behaviors.push(ObstacleBehavior::Orbit(orbit.clone()));
+    }
+    if let Some(waypoints) = waypoints {
+        behaviors.push(ObstacleBehavior::Waypoints(waypoints.clone()));
+    }
+    behaviors
+}
+
+// An obstacle placed in a level file; it flies in a straight line along `direction` (random if left out)
+// unless its behaviors steer it
+#[derive(Deserialize)]
+pub struct ObstacleSpawn {
+    pub position: Vec2,
+    #[serde(default)]
+    pub direction: Option<Vec2>,
+    #[serde(default)]
+    pub behaviors: Vec<ObstacleBehavior>,
+}
+
+// System to turn homing obstacles toward the nearest ship, no faster than their turn rate
+pub fn home_obstacles(
+    time: Res<Time>,
+    ships: Query<&Transform, With<Ship>>,
+    mut obstacles: Query<(&Homing, &Transform, &mut BoxDirection), (With<BoxEntity>, Without<Ship>)>,
+) {
+    for (homing, transform, mut direction) in obstacles.iter_mut() {
+        let Some(ship) = nearest_ship(ships.iter(), transform.translation.truncate()) else {
+            return;
+        };
+        let current = direction.0.truncate();
+        let desired = ship - transform.translation.truncate();
+        if current == Vec2::ZERO || desired == Vec2::ZERO {
+            direction.0 = desired.normalize_or_zero().extend(0.0);
+            continue;
+        }
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let turn = current.angle_between(desired).clamp(-max_turn, max_turn);
+        direction.0 = Vec2::from_angle(turn).rotate(current).normalize().extend(0.0);
+    }
+}
+
+// System to steer orbiting obstacles along their circle, drifting back onto it when off it
+pub fn orbit_obstacles(mut obstacles: Query<(&Orbit, &Transform, &mut BoxDirection), With<BoxEntity>>) {
+    for (orbit, transform, mut direction) in obstacles.iter_mut() {
+        let to_center = orbit.center - transform.translation.truncate();
+        let inward = to_center.normalize_or_zero();
+        let tangent = if orbit.clockwise { -inward.perp() } else { inward.perp() };
+        let drift = ((to_center.length() - orbit.radius) / orbit.radius.max(1.0)).clamp(-1.0, 1.0);
+        direction.0 = (tangent + inward * drift).normalize_or_zero().extend(0.0);
+    }
+}
+
+// System to head each waypoint obstacle for its next point, moving on once it gets there
+pub fn follow_waypoints(mut obstacles: Query<(&mut Waypoints, &Transform, &mut BoxDirection), With<BoxEntity>>) {
+    for (mut waypoints, transform, mut direction) in obstacles.iter_mut() {
+        if waypoints.points.is_empty() {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let mut target = waypoints.points[waypoints.next % waypoints.points.len()];
+        if position.distance(target) < WAYPOINT_REACHED {
+            waypoints.next = (waypoints.next + 1) % waypoints.points.len();
+            target = waypoints.points[waypoints.next];
+        }
+        direction.0 = (target - position).normalize_or_zero().extend(0.0);
+    }
+}
+
+// System to sway obstacles sideways along their sine wave
+pub fn weave_obstacles(
+    time: Res<Time>,
+    game_timer: Res<GameTimer>,
+    mut obstacles: Query<(&mut SineWave, &mut Transform, &BoxDirection), With<BoxEntity>>,
+) {
+    // Obstacles stop with the boxes once the game is over
+    if game_timer.1 {
+        return;
+    }
+    for (mut sine_wave, mut transform, direction) in obstacles.iter_mut() {
+        let offset = |elapsed: f32| sine_wave.amplitude * (elapsed * sine_wave.frequency * TAU).sin();
+        let before = offset(sine_wave.elapsed);
+        let after = offset(sine_wave.elapsed + time.delta_seconds());
+        let side = direction.0.truncate().perp();
+        transform.translation += (side * (after - before)).extend(0.0);
+        sine_wave.elapsed += time.delta_seconds();
+    }
+}
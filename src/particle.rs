@@ -0,0 +1,206 @@
+// particle.rs
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use rand::Rng;
+use crate::component::LevelEntity;
+use crate::state::GameplaySet;
+use crate::system::{BoxDestroyed, LaserImpact};
+
+// Plugin for CPU-simulated sprite particles: engine exhaust, box debris and laser sparks
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                run_emitters,     // Continuous emitters such as the engine exhaust
+                spawn_debris,     // Debris where boxes were destroyed
+                spawn_sparks,     // Sparks where lasers hit
+                update_particles, // Move, fade and remove particles
+            )
+                .in_set(GameplaySet),
+        );
+    }
+}
+
+// How a kind of particle is emitted and how it changes over its life
+#[derive(Clone, Copy)]
+pub struct ParticleEffect {
+    pub rate: f32,             // Particles per second from a continuous emitter
+    pub burst: u32,            // Particles per one-off burst
+    pub lifetime: (f32, f32),  // Seconds, picked at random between the two
+    pub speed: (f32, f32),     // Initial speed, picked at random between the two
+    pub spread: f32,           // Radians either side of the emitter's direction
+    pub color: (Color, Color), // At birth and at the end of life
+    pub size: (f32, f32),      // At birth and at the end of life
+    pub drag: f32,             // Share of the velocity lost per second
+}
+
+// Engine exhaust trailing the ship
+pub const EXHAUST: ParticleEffect = ParticleEffect {
+    rate: 60.0,
+    burst: 0,
+    lifetime: (0.3, 0.5),
+    speed: (60.0, 120.0),
+    spread: 0.3,
+    color: (Color::rgba(1.0, 0.8, 0.3, 0.9), Color::rgba(0.8, 0.2, 0.1, 0.0)),
+    size: (6.0, 1.0),
+    drag: 1.0,
+};
+
+// Fragments flying off a destroyed box
+pub const DEBRIS: ParticleEffect = ParticleEffect {
+    rate: 0.0,
+    burst: 12,
+    lifetime: (0.5, 1.0),
+    speed: (50.0, 180.0),
+    spread: PI,
+    color: (Color::rgba(0.8, 0.6, 0.3, 1.0), Color::rgba(0.4, 0.3, 0.2, 0.0)),
+    size: (5.0, 2.0),
+    drag: 2.0,
+};
+
+// Sparks thrown back from a laser hit
+pub const SPARKS: ParticleEffect = ParticleEffect {
+    rate: 0.0,
+    burst: 8,
+    lifetime: (0.15, 0.35),
+    speed: (150.0, 300.0),
+    spread: 0.8,
+    color: (Color::rgba(1.0, 1.0, 0.7, 1.0), Color::rgba(1.0, 0.5, 0.0, 0.0)),
+    size: (3.0, 1.0),
+    drag: 4.0,
+};
+
+// Continuously emits particles along `direction` (in its own rotated frame) while active
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    pub direction: Vec2,
+    pub active: bool,
+    pending: f32, // Fraction of a particle carried over to the next frame
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: ParticleEffect, direction: Vec2) -> Self {
+        ParticleEmitter {
+            effect,
+            direction,
+            active: false,
+            pending: 0.0,
+        }
+    }
+}
+
+// A single particle, living in world space
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    effect: ParticleEffect,
+}
+
+// Spawns one particle heading roughly along `direction`
+fn spawn_particle(commands: &mut Commands, effect: &ParticleEffect, position: Vec3, direction: Vec2) {
+    let mut rng = rand::thread_rng();
+    let angle = if effect.spread > 0.0 { rng.gen_range(-effect.spread..effect.spread) } else { 0.0 };
+    let speed = rng.gen_range(effect.speed.0..=effect.speed.1);
+    let velocity = Vec2::from_angle(angle).rotate(direction.normalize_or_zero()) * speed;
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: effect.color.0,
+                custom_size: Some(Vec2::splat(effect.size.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        },
+        Particle {
+            velocity,
+            age: 0.0,
+            lifetime: rng.gen_range(effect.lifetime.0..=effect.lifetime.1),
+            effect: *effect,
+        },
+        LevelEntity,
+    ));
+}
+
+// Spawns a one-off burst of particles
+pub fn spawn_burst(commands: &mut Commands, effect: &ParticleEffect, position: Vec3, direction: Vec2) {
+    for _ in 0..effect.burst {
+        spawn_particle(commands, effect, position, direction);
+    }
+}
+
+// System to emit particles from every active emitter at its rate
+pub fn run_emitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut emitters: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+) {
+    for (mut emitter, transform) in emitters.iter_mut() {
+        if !emitter.active {
+            continue;
+        }
+        emitter.pending += emitter.effect.rate * time.delta_seconds();
+
+        let (_, rotation, position) = transform.to_scale_rotation_translation();
+        let direction = (rotation * emitter.direction.extend(0.0)).truncate();
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+            spawn_particle(&mut commands, &emitter.effect, position, direction);
+        }
+    }
+}
+
+// System to scatter debris where boxes were destroyed
+pub fn spawn_debris(mut commands: Commands, mut box_destroyed: EventReader<BoxDestroyed>) {
+    for event in box_destroyed.iter() {
+        spawn_burst(&mut commands, &DEBRIS, event.translation, Vec2::Y);
+    }
+}
+
+// System to throw sparks back from where lasers hit
+pub fn spawn_sparks(mut commands: Commands, mut laser_impacts: EventReader<LaserImpact>) {
+    for event in laser_impacts.iter() {
+        spawn_burst(&mut commands, &SPARKS, event.translation, -event.direction);
+    }
+}
+
+// System to move particles, blend their color and size over their life and remove the expired ones
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let drag = (1.0 - particle.effect.drag * delta).max(0.0);
+        particle.velocity *= drag;
+        transform.translation += particle.velocity.extend(0.0) * delta;
+
+        let t = particle.age / particle.lifetime;
+        let (start, end) = particle.effect.color;
+        sprite.color = Color::rgba(
+            lerp(start.r(), end.r(), t),
+            lerp(start.g(), end.g(), t),
+            lerp(start.b(), end.b(), t),
+            lerp(start.a(), end.a(), t),
+        );
+        sprite.custom_size = Some(Vec2::splat(lerp(particle.effect.size.0, particle.effect.size.1, t)));
+    }
+}
+
+fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    start + (end - start) * t
+}
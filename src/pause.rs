@@ -0,0 +1,123 @@
+// pause.rs
+use bevy::prelude::*;
+use crate::config::GameConfig;
+use crate::profile::PlayerProfile;
+use crate::settings::{Settings, SettingsMenuState};
+use crate::state::{AppState, PauseState};
+use crate::ui::{spawn_button, spawn_menu_root, spawn_title, ButtonActivated, MenuInput, FONT_PATH};
+
+// Plugin for pausing the game and the pause menu overlay
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Paused), (pause_time, spawn_pause_menu))
+            .add_systems(OnExit(PauseState::Paused), (resume_time, despawn_pause_menu))
+            // Hide the pause menu while the settings menu is on top of it
+            .add_systems(OnEnter(SettingsMenuState::Open), despawn_pause_menu)
+            // Bring it back when the settings menu closes; not on `OnEnter(Closed)`, which also runs at startup
+            .add_systems(
+                OnTransition {
+                    from: SettingsMenuState::Open,
+                    to: SettingsMenuState::Closed,
+                },
+                spawn_pause_menu.run_if(in_state(PauseState::Paused)).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,        // Esc/Start pauses and resumes the game
+                    handle_pause_buttons, // Resume, Restart, Settings and Quit
+                )
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(in_state(SettingsMenuState::Closed)),
+            );
+    }
+}
+
+// Root node of the pause menu
+#[derive(Component)]
+pub struct PauseMenu;
+
+// Buttons of the pause menu
+#[derive(Component, Clone, Copy)]
+pub enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+// System to stop the clock so no time passes for gameplay while paused
+pub fn pause_time(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+// System to restart the clock; the first frame after resuming only sees that frame's delta
+pub fn resume_time(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+// System to toggle the pause state from the keyboard or a gamepad
+pub fn toggle_pause(
+    menu_input: MenuInput,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if menu_input.pause() {
+        next_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+// System to build the pause menu overlay
+pub fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(FONT_PATH);
+    let root = spawn_menu_root(&mut commands, Color::rgba(0.0, 0.0, 0.0, 0.6));
+    commands.entity(root).insert(PauseMenu).with_children(|parent| {
+        spawn_title(parent, &font, "Paused");
+        spawn_button(parent, &font, "Resume", 0, PauseButton::Resume);
+        spawn_button(parent, &font, "Restart", 1, PauseButton::Restart);
+        spawn_button(parent, &font, "Settings", 2, PauseButton::Settings);
+        spawn_button(parent, &font, "Save & Quit", 3, PauseButton::Quit);
+    });
+}
+
+// System to remove the pause menu overlay
+pub fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// System to act on pause menu button presses
+pub fn handle_pause_buttons(
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<&PauseButton>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_settings_state: ResMut<NextState<SettingsMenuState>>,
+    mut profile: ResMut<PlayerProfile>,
+    settings: Res<Settings>,
+    config: Res<GameConfig>,
+) {
+    for event in activated.iter() {
+        if let Ok(button) = buttons.get(event.0) {
+            match button {
+                PauseButton::Resume => next_pause_state.set(PauseState::Running),
+                PauseButton::Restart => {
+                    *profile = PlayerProfile::new(settings.difficulty, profile.players.len(), &config.difficulty);
+                    next_pause_state.set(PauseState::Running);
+                    next_app_state.set(AppState::Restarting);
+                }
+                PauseButton::Settings => next_settings_state.set(SettingsMenuState::Open),
+                PauseButton::Quit => {
+                    next_pause_state.set(PauseState::Running);
+                    next_app_state.set(AppState::MainMenu);
+                }
+            }
+        }
+    }
+}
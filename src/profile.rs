@@ -0,0 +1,166 @@
+// profile.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::config::{DifficultyConfig, ExtraLifeConfig, GameConfig};
+use crate::difficulty::Difficulty;
+use crate::state::{GameMode, GameplaySet};
+
+// Continues available at the start of a run
+pub const STARTING_CONTINUES: u32 = 2;
+
+// Most players that can share a run; each one needs its own controls in `input.rs`
+pub const MAX_PLAYERS: usize = 2;
+
+// Plugin for the player profile and its life events
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerProfile>()
+            .add_event::<LifeLost>()
+            .add_event::<LifeGained>()
+            .add_systems(Update, award_extra_lives.in_set(GameplaySet));
+    }
+}
+
+// One player's lives and score
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerStats {
+    pub lives: u32,
+    pub score: i32,
+    pub extra_lives_awarded: usize, // Number of score milestones already paid out
+    pub kills: u32,                 // Boxes, enemies and bosses destroyed by the player's lasers
+}
+
+impl PlayerStats {
+    fn new(lives: u32) -> Self {
+        PlayerStats {
+            lives,
+            score: 0,
+            extra_lives_awarded: 0,
+            kills: 0,
+        }
+    }
+
+    // Score needed for the next extra life; `config` has at least one threshold once validated
+    pub fn next_extra_life_score(&self, config: &ExtraLifeConfig) -> i32 {
+        let awarded = self.extra_lives_awarded;
+        match config.thresholds.get(awarded) {
+            Some(threshold) => *threshold,
+            None => {
+                let past_last = (awarded - config.thresholds.len() + 1) as i32;
+                config.thresholds[config.thresholds.len() - 1] + past_last * config.interval
+            }
+        }
+    }
+}
+
+// The run state; the single source of truth for every player's lives and score, independent of the ship entities
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct PlayerProfile {
+    pub players: Vec<PlayerStats>, // One entry per player, in player order
+    pub continues: u32,            // Shared by all players
+    #[serde(default)]
+    pub difficulty: Difficulty, // Preset the run was started on
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        PlayerProfile::new(Difficulty::default(), 1, &DifficultyConfig::default())
+    }
+}
+
+impl PlayerProfile {
+    // A fresh run on the given difficulty
+    pub fn new(difficulty: Difficulty, player_count: usize, config: &DifficultyConfig) -> Self {
+        let lives = config.preset(difficulty).starting_lives;
+        PlayerProfile {
+            players: vec![PlayerStats::new(lives); player_count.clamp(1, MAX_PLAYERS)],
+            continues: STARTING_CONTINUES,
+            difficulty,
+        }
+    }
+
+    // True when more than one player shares the run
+    pub fn co_op(&self) -> bool {
+        self.players.len() > 1
+    }
+
+    // Combined score of all players, which is what goes on the leaderboards
+    pub fn score(&self) -> i32 {
+        self.players.iter().map(|player| player.score).sum()
+    }
+
+    pub fn kills(&self) -> u32 {
+        self.players.iter().map(|player| player.kills).sum()
+    }
+
+    // True once every player is out of lives
+    pub fn all_out(&self) -> bool {
+        self.players.iter().all(|player| player.lives == 0)
+    }
+
+    // Spends a continue to get every player a full set of lives back, starting the scores over
+    pub fn use_continue(&mut self, config: &DifficultyConfig) -> bool {
+        if self.continues == 0 {
+            return false;
+        }
+        *self = PlayerProfile {
+            continues: self.continues - 1,
+            ..PlayerProfile::new(self.difficulty, self.players.len(), config)
+        };
+        true
+    }
+}
+
+// Event fired when a player loses a life, with the lives they have left
+#[derive(Event)]
+pub struct LifeLost {
+    pub player: usize,
+    pub lives: u32,
+}
+
+// Event fired when a player earns an extra life, with their new total
+#[derive(Event)]
+pub struct LifeGained {
+    pub player: usize,
+    pub lives: u32,
+}
+
+// System to hand out extra lives as each player's score passes each milestone; versus rounds are played
+// on a fixed number of lives
+pub fn award_extra_lives(
+    mut profile: ResMut<PlayerProfile>,
+    mode: Res<GameMode>,
+    config: Res<GameConfig>,
+    mut life_gained: EventWriter<LifeGained>,
+) {
+    if !profile.is_changed() || *mode == GameMode::Versus {
+        return;
+    }
+    for (index, player) in profile.players.iter_mut().enumerate() {
+        while player.score >= player.next_extra_life_score(&config.extra_lives) {
+            player.extra_lives_awarded += 1;
+            player.lives += 1;
+            life_gained.send(LifeGained { player: index, lives: player.lives });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_lives_follow_the_thresholds_then_every_interval() {
+        let config = ExtraLifeConfig::default();
+        let mut stats = PlayerStats::new(3);
+        let expected = [10, 25, 50, 100, 150, 200];
+        for (awarded, score) in expected.into_iter().enumerate() {
+            stats.extra_lives_awarded = awarded;
+            assert_eq!(stats.next_extra_life_score(&config), score, "after {} extra lives", awarded);
+        }
+        stats.extra_lives_awarded = 10;
+        assert_eq!(stats.next_extra_life_score(&config), 450);
+    }
+}
@@ -0,0 +1,515 @@
+// results.rs
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use crate::component::{GameTimer, Ship};
+use crate::config::GameConfig;
+use crate::level::{CurrentLevel, Levels, Progress, PROGRESS_FILE};
+use crate::profile::PlayerProfile;
+use crate::settings::Settings;
+use crate::state::{AppState, GameMode};
+use crate::storage;
+use crate::timeattack::{BestRun, Medal, Splits, TimeAttackResult};
+use crate::versus::RoundResult;
+use crate::ui::{spawn_button, spawn_menu_root, spawn_title, ButtonActivated, MenuInput, FONT_PATH};
+
+// Plugin for the game-over and level-complete screens, including leaderboard entry
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Results),
+            (record_run, apply_deferred, spawn_results_screen).chain(),
+        )
+        .add_systems(OnExit(AppState::Results), despawn_results_screen)
+        .add_systems(
+            Update,
+            (
+                handle_initials_input.run_if(resource_exists::<InitialsEntry>()), // Type in initials for a new record
+                handle_results_buttons,                                         // Next Level, Retry and Main Menu
+            )
+                .run_if(in_state(AppState::Results)),
+        );
+    }
+}
+
+// How the last run ended, set by gameplay systems before switching to `AppState::Results`
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    LevelComplete,
+    GameOver,
+}
+
+// Leaderboard entries waiting for the player to enter their initials
+#[derive(Resource)]
+pub struct InitialsEntry {
+    letters: [char; 3],
+    slot: usize,
+    score: Option<i32>,    // Score to record, if it made the leaderboard
+    time: Option<f32>,     // Completion time to record, if it made the leaderboard
+    survival: Option<f32>, // Time survived to record along with the kills, if it made the leaderboard
+}
+
+// Root node of the results screen
+#[derive(Component)]
+pub struct ResultsScreen;
+
+// Text showing the initials being entered
+#[derive(Component)]
+pub struct InitialsText;
+
+// Buttons of the results screen
+#[derive(Component, Clone, Copy)]
+pub enum ResultsButton {
+    NextLevel,
+    Continue,
+    Retry,
+    MainMenu,
+}
+
+// System to unlock the next level and check whether the run made a leaderboard
+pub fn record_run(
+    mut commands: Commands,
+    outcome: Res<RunOutcome>,
+    profile: Res<PlayerProfile>,
+    timer: Res<GameTimer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+    splits: Option<Res<Splits>>,
+    mut progress: ResMut<Progress>,
+) {
+    let level = &levels.0[current_level.0];
+    let level_name = &level.name;
+
+    // Survival runs only go on the survival leaderboard, and time attack runs only count as the best run
+    let (score, time, survival) = match (*mode, *outcome) {
+        (GameMode::Survival, _) => {
+            let survival = timer.0.filter(|time| progress.survival_qualifies(level_name, *time, profile.kills()));
+            (None, None, survival)
+        }
+        (GameMode::Campaign, outcome) => {
+            let time = match outcome {
+                RunOutcome::LevelComplete => {
+                    progress.unlock_after(current_level.0, levels.0.len());
+                    timer.0.filter(|time| progress.time_qualifies(level_name, *time))
+                }
+                RunOutcome::GameOver => None,
+            };
+            let score = Some(profile.score()).filter(|score| progress.score_qualifies(level_name, *score));
+            (score, time, None)
+        }
+        (GameMode::TimeAttack, RunOutcome::LevelComplete) => {
+            let time = timer.0.unwrap_or(0.0);
+            let mut run_splits = splits.map(|splits| splits.times.clone()).unwrap_or_default();
+            run_splits.push(time);
+            let run = BestRun {
+                time,
+                splits: run_splits,
+                medal: level.time_attack.as_ref().and_then(|time_attack| time_attack.par.medal(time)),
+            };
+            let previous = progress.best_run(level_name).cloned();
+            progress.record_best_run(level_name, run.clone());
+            commands.insert_resource(TimeAttackResult { run, previous });
+            (None, None, None)
+        }
+        (GameMode::TimeAttack, RunOutcome::GameOver) => (None, None, None),
+        (GameMode::Versus, _) => (None, None, None), // Rounds aren't ranked
+    };
+
+    if score.is_some() || time.is_some() || survival.is_some() {
+        let mut letters = ['A'; 3];
+        for (letter, initial) in letters.iter_mut().zip(progress.initials.chars()) {
+            *letter = initial;
+        }
+        commands.insert_resource(InitialsEntry { letters, slot: 0, score, time, survival });
+    } else {
+        storage::save(PROGRESS_FILE, &*progress);
+    }
+}
+
+// System to build the results screen
+pub fn spawn_results_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    outcome: Res<RunOutcome>,
+    profile: Res<PlayerProfile>,
+    timer: Res<GameTimer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+    progress: Res<Progress>,
+    entry: Option<Res<InitialsEntry>>,
+    time_attack: Option<Res<TimeAttackResult>>,
+    round: Option<Res<RoundResult>>,
+) {
+    build_results_screen(
+        &mut commands,
+        asset_server.load(FONT_PATH),
+        *outcome,
+        *mode,
+        &profile,
+        timer.0.unwrap_or(0.0),
+        &levels,
+        current_level.0,
+        &progress,
+        time_attack.as_deref(),
+        round.as_deref(),
+        entry.is_some(),
+    );
+}
+
+// Builds either the initials prompt or the leaderboards with the follow-up buttons
+fn build_results_screen(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    outcome: RunOutcome,
+    mode: GameMode,
+    profile: &PlayerProfile,
+    time: f32,
+    levels: &Levels,
+    level_index: usize,
+    progress: &Progress,
+    time_attack: Option<&TimeAttackResult>,
+    round: Option<&RoundResult>,
+    entering_initials: bool,
+) {
+    let level_name = &levels.0[level_index].name;
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 26.0,
+        color: Color::WHITE,
+    };
+
+    let root = spawn_menu_root(commands, Color::rgba(0.0, 0.0, 0.0, 0.85));
+    commands.entity(root).insert(ResultsScreen).with_children(|parent| {
+        match (mode, outcome) {
+            (GameMode::Versus, _) => match round.and_then(|round| round.winner) {
+                Some(winner) => spawn_title(parent, &font, &format!("Player {} Wins!", winner + 1)),
+                None => spawn_title(parent, &font, "Draw!"),
+            },
+            (_, RunOutcome::LevelComplete) => spawn_title(parent, &font, &format!("{} Complete", level_name)),
+            (_, RunOutcome::GameOver) => spawn_title(parent, &font, "Game Over!"),
+        }
+
+        let summary = match (mode, outcome) {
+            (GameMode::Survival, _) => {
+                format!("Survived: {:.2} seconds    Kills: {}    Score: {}", time, profile.kills(), profile.score())
+            }
+            (GameMode::Campaign, RunOutcome::LevelComplete) => {
+                format!("Score: {}    Time: {:.2} seconds", profile.score(), time)
+            }
+            (GameMode::Campaign, RunOutcome::GameOver) => format!("Score: {}", profile.score()),
+            (GameMode::TimeAttack, RunOutcome::LevelComplete) => format!("Time: {:.2} seconds", time),
+            (GameMode::TimeAttack, RunOutcome::GameOver) => "Out of lives - no time recorded".to_string(),
+            (GameMode::Versus, _) if round.is_some_and(|round| round.time_up) => "Time's up!".to_string(),
+            (GameMode::Versus, _) => "Last ship standing".to_string(),
+        };
+        parent.spawn(TextBundle::from_section(summary, text_style.clone()));
+
+        // In co-op, each player's share of the team's score
+        if profile.co_op() && mode != GameMode::TimeAttack {
+            for (player, stats) in profile.players.iter().enumerate() {
+                let lives = if mode == GameMode::Versus { format!("    Lives left: {}", stats.lives) } else { String::new() };
+                parent.spawn(TextBundle::from_section(
+                    format!("P{}    Score: {}    Kills: {}{}", player + 1, stats.score, stats.kills, lives),
+                    TextStyle { color: Ship(player).color(), ..text_style.clone() },
+                ));
+            }
+        }
+
+        if mode == GameMode::TimeAttack {
+            spawn_time_attack_summary(parent, &text_style, levels, level_index, progress, time_attack);
+            spawn_button(parent, &font, "Retry", 0, ResultsButton::Retry);
+            spawn_button(parent, &font, "Main Menu", 1, ResultsButton::MainMenu);
+            return;
+        }
+
+        // A versus round goes straight to the rematch, with no leaderboards
+        if mode == GameMode::Versus {
+            spawn_button(parent, &font, "Rematch", 0, ResultsButton::Retry);
+            spawn_button(parent, &font, "Main Menu", 1, ResultsButton::MainMenu);
+            return;
+        }
+
+        if entering_initials {
+            parent.spawn(TextBundle::from_section("New record! Enter your initials", text_style.clone()));
+            parent.spawn((
+                TextBundle::from_sections((0..3).map(|_| TextSection::new("", TextStyle {
+                    font_size: 60.0,
+                    ..text_style.clone()
+                }))),
+                InitialsText,
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Type letters or use Up/Down and Left/Right, then press Enter",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::GRAY,
+                    ..text_style.clone()
+                },
+            ));
+            return;
+        }
+
+        // Leaderboards side by side
+        let records = progress.records(level_name).cloned().unwrap_or_default();
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(60.0),
+                    margin: UiRect::vertical(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|columns| {
+                if mode == GameMode::Survival {
+                    let mut survival = String::from("Longest Survivals\n");
+                    for (rank, record) in records.survival.iter().enumerate() {
+                        survival.push_str(&format!(
+                            "{}. {}  {:.2} s  {} kills\n",
+                            rank + 1,
+                            record.initials,
+                            record.time,
+                            record.kills
+                        ));
+                    }
+                    columns.spawn(TextBundle::from_section(survival, text_style.clone()));
+                    return;
+                }
+
+                let mut scores = String::from("Top Scores\n");
+                for (rank, record) in records.scores.iter().enumerate() {
+                    scores.push_str(&format!("{}. {}  {}\n", rank + 1, record.initials, record.score));
+                }
+                columns.spawn(TextBundle::from_section(scores, text_style.clone()));
+
+                let mut times = String::from("Best Times\n");
+                for (rank, record) in records.times.iter().enumerate() {
+                    times.push_str(&format!("{}. {}  {:.2} s\n", rank + 1, record.initials, record.time));
+                }
+                columns.spawn(TextBundle::from_section(times, text_style.clone()));
+            });
+
+        let mut order = 0;
+        if outcome == RunOutcome::LevelComplete && level_index + 1 < levels.0.len() {
+            spawn_button(parent, &font, "Next Level", order, ResultsButton::NextLevel);
+            order += 1;
+        }
+        // A continue would start the clock over, so survival runs can't use one
+        if outcome == RunOutcome::GameOver && mode == GameMode::Campaign && profile.continues > 0 {
+            let label = format!("Continue ({} left)", profile.continues);
+            spawn_button(parent, &font, &label, order, ResultsButton::Continue);
+            order += 1;
+        }
+        spawn_button(parent, &font, "Retry", order, ResultsButton::Retry);
+        spawn_button(parent, &font, "Main Menu", order + 1, ResultsButton::MainMenu);
+    });
+}
+
+// Adds the medal earned, this run's splits against the best run's and the level's par times
+fn spawn_time_attack_summary(
+    parent: &mut ChildBuilder,
+    text_style: &TextStyle,
+    levels: &Levels,
+    level_index: usize,
+    progress: &Progress,
+    result: Option<&TimeAttackResult>,
+) {
+    let level = &levels.0[level_index];
+    let par = level.time_attack.as_ref().map(|time_attack| time_attack.par);
+
+    if let Some(result) = result {
+        let (medal_text, color) = match (result.run.medal, par) {
+            (Some(medal), _) => (format!("{} medal!", medal.name()), medal.color()),
+            (None, Some(par)) => (format!("No medal - bronze par is {:.2} s", par.bronze), Color::GRAY),
+            (None, None) => ("This level has no par times".to_string(), Color::GRAY),
+        };
+        parent.spawn(TextBundle::from_section(medal_text, TextStyle {
+            font_size: 40.0,
+            color,
+            ..text_style.clone()
+        }));
+        let best_text = match &result.previous {
+            Some(previous) if result.new_best() => {
+                format!("New best run! {:.2} s faster", previous.time - result.run.time)
+            }
+            Some(previous) => format!("Best run: {:.2} s", previous.time),
+            None => "New best run!".to_string(),
+        };
+        parent.spawn(TextBundle::from_section(best_text, text_style.clone()));
+    }
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(60.0),
+                margin: UiRect::vertical(Val::Px(20.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|columns| {
+            // Splits of this run, each against the same split of the best run before it
+            if let Some(result) = result {
+                let count = result.run.splits.len();
+                let mut splits = String::from("Splits\n");
+                for (index, split) in result.run.splits.iter().enumerate() {
+                    let name = if index + 1 == count { "Finish".to_string() } else { format!("{}.", index + 1) };
+                    splits.push_str(&format!("{}  {:.2} s", name, split));
+                    let best = result.previous.as_ref().and_then(|previous| previous.splits.get(index));
+                    if let Some(best) = best {
+                        splits.push_str(&format!("  ({:+.2})", split - best));
+                    }
+                    splits.push('\n');
+                }
+                columns.spawn(TextBundle::from_section(splits, text_style.clone()));
+            } else if let Some(best) = progress.best_run(&level.name) {
+                columns.spawn(TextBundle::from_section(format!("Best Run\n{:.2} s\n", best.time), text_style.clone()));
+            }
+
+            if let Some(par) = par {
+                let mut pars = String::from("Par Times\n");
+                for medal in [Medal::Gold, Medal::Silver, Medal::Bronze] {
+                    pars.push_str(&format!("{}  {:.2} s\n", medal.name(), par.time(medal)));
+                }
+                columns.spawn(TextBundle::from_section(pars, text_style.clone()));
+            }
+        });
+}
+
+// System to remove the results screen
+pub fn despawn_results_screen(mut commands: Commands, query: Query<Entity, With<ResultsScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// System to edit the initials and, once confirmed, store the new records
+pub fn handle_initials_input(
+    mut commands: Commands,
+    mut characters: EventReader<ReceivedCharacter>,
+    menu_input: MenuInput,
+    keyboard: Res<Input<KeyCode>>,
+    mut entry: ResMut<InitialsEntry>,
+    mut initials_text: Query<&mut Text, With<InitialsText>>,
+    screen: Query<Entity, With<ResultsScreen>>,
+    asset_server: Res<AssetServer>,
+    outcome: Res<RunOutcome>,
+    profile: Res<PlayerProfile>,
+    timer: Res<GameTimer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+    mut progress: ResMut<Progress>,
+) {
+    // Typed letters fill the current slot and move on; they take precedence over W/S navigation
+    let mut typed = false;
+    for character in characters.iter() {
+        if character.char.is_ascii_alphabetic() {
+            let slot = entry.slot;
+            entry.letters[slot] = character.char.to_ascii_uppercase();
+            entry.slot = (slot + 1).min(2);
+            typed = true;
+        }
+    }
+
+    if !typed {
+        let slot = entry.slot;
+        if menu_input.up() {
+            entry.letters[slot] = cycle_letter(entry.letters[slot], 1);
+        }
+        if menu_input.down() {
+            entry.letters[slot] = cycle_letter(entry.letters[slot], 25);
+        }
+        if menu_input.left() || keyboard.just_pressed(KeyCode::Back) {
+            entry.slot = slot.saturating_sub(1);
+        }
+        if menu_input.right() {
+            entry.slot = (slot + 1).min(2);
+        }
+    }
+
+    if let Ok(mut text) = initials_text.get_single_mut() {
+        for (index, section) in text.sections.iter_mut().enumerate() {
+            section.value = format!(" {} ", entry.letters[index]);
+            section.style.color = if index == entry.slot { Color::YELLOW } else { Color::WHITE };
+        }
+    }
+
+    if menu_input.confirm() {
+        let initials: String = entry.letters.iter().collect();
+        let level_name = &levels.0[current_level.0].name;
+        if let Some(score) = entry.score {
+            progress.add_score(level_name, &initials, score);
+        }
+        if let Some(time) = entry.time {
+            progress.add_time(level_name, &initials, time);
+        }
+        if let Some(time) = entry.survival {
+            progress.add_survival(level_name, &initials, time, profile.kills());
+        }
+        progress.initials = initials;
+        storage::save(PROGRESS_FILE, &*progress);
+
+        // Swap the prompt for the updated leaderboards
+        commands.remove_resource::<InitialsEntry>();
+        for entity in screen.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        build_results_screen(
+            &mut commands,
+            asset_server.load(FONT_PATH),
+            *outcome,
+            *mode,
+            &profile,
+            timer.0.unwrap_or(0.0),
+            &levels,
+            current_level.0,
+            &progress,
+            None,
+            None,
+            false,
+        );
+    }
+}
+
+// Steps a letter through A-Z, wrapping around
+fn cycle_letter(letter: char, step: u8) -> char {
+    let index = (letter as u8).wrapping_sub(b'A') % 26;
+    (b'A' + (index + step) % 26) as char
+}
+
+// System to act on results screen button presses
+pub fn handle_results_buttons(
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<&ResultsButton>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut profile: ResMut<PlayerProfile>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+    config: Res<GameConfig>,
+) {
+    for event in activated.iter() {
+        if let Ok(button) = buttons.get(event.0) {
+            match button {
+                // Lives, score and continues carry over into the next level
+                ResultsButton::NextLevel => {
+                    current_level.0 += 1;
+                    next_state.set(AppState::InGame);
+                }
+                ResultsButton::Continue => {
+                    if profile.use_continue(&config.difficulty) {
+                        next_state.set(AppState::InGame);
+                    }
+                }
+                ResultsButton::Retry => {
+                    *profile = PlayerProfile::new(settings.difficulty, profile.players.len(), &config.difficulty);
+                    next_state.set(AppState::InGame);
+                }
+                ResultsButton::MainMenu => next_state.set(AppState::MainMenu),
+            }
+        }
+    }
+}
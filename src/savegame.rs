@@ -0,0 +1,317 @@
+// savegame.rs
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use serde::{Deserialize, Serialize};
+use crate::config::GameConfig;
+use crate::component::{
+    BoxDirection, BoxEntity, EndPoint, Faction, GameTimer, Laser, LaserType, Locked, Ship,
+};
+use crate::animation::Animations;
+use crate::boss::{Boss, WeakPoint};
+use crate::enemy::{spawn_enemy, Enemy, EnemyAi, EnemySpawn};
+use crate::input::spawn_laser;
+use crate::obstacle::{obstacle_behaviors, ObstacleBehavior, ObstacleComponents};
+use crate::level::{CurrentLevel, Levels};
+use crate::profile::PlayerProfile;
+use crate::state::{AppState, GameMode, PauseState};
+use crate::storage;
+use crate::system::{cleanup_level, setup, spawn_box, spawn_ship};
+use crate::wave::{WaveSnapshot, WaveSpawner};
+
+// Save file for a run in progress, inside the data dir
+pub const RUN_FILE: &str = "run.ron";
+
+// Plugin for saving a run when leaving it mid-level and restoring it with Continue
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            (
+                apply_deferred,                                             // Let the fresh level spawn first
+                discard_saved_run.run_if(resource_exists::<RunSnapshot>()), // A save can only be continued once
+                restore_run.run_if(resource_exists::<RunSnapshot>()),       // Replace the fresh level with the saved one
+            )
+                .chain()
+                .after(setup),
+        )
+        .add_systems(
+            OnExit(AppState::InGame),
+            // Quitting to the menu keeps the run; restarts and finished runs don't
+            save_run.run_if(in_state(AppState::MainMenu)).before(cleanup_level),
+        )
+        .add_systems(Update, save_run_on_close.run_if(in_state(AppState::InGame)));
+    }
+}
+
+// Saved ship placement, with the player flying it
+#[derive(Serialize, Deserialize)]
+pub struct ShipSnapshot {
+    #[serde(default)]
+    pub player: usize,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+// Saved box placement and heading
+#[derive(Serialize, Deserialize)]
+pub struct BoxSnapshot {
+    pub translation: Vec3,
+    pub direction: Vec3,
+    #[serde(default)]
+    pub behaviors: Vec<ObstacleBehavior>,
+}
+
+// Saved laser in flight
+#[derive(Serialize, Deserialize)]
+pub struct LaserSnapshot {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub laser_type: LaserType,
+    #[serde(default)]
+    pub faction: Faction,
+}
+
+// Saved enemy, with the level entry it came from and what it was doing
+#[derive(Serialize, Deserialize)]
+pub struct EnemySnapshot {
+    pub spawn: EnemySpawn,
+    pub translation: Vec2,
+    pub health: u32,
+    #[serde(default)]
+    pub ai: Option<EnemyAi>,
+}
+
+// Saved state of the level's boss: whether it is already beaten, or else the health of its weak points in
+// their order on the hull
+#[derive(Serialize, Deserialize)]
+pub struct BossSnapshot {
+    #[serde(default)]
+    pub defeated: bool,
+    #[serde(default)]
+    pub weak_points: Vec<u32>,
+}
+
+// Everything that plays a part in a run, to put it back as it was; short-lived effects such as fireballs and
+// particles are left out. Inserted as a resource to be restored
+#[derive(Resource, Serialize, Deserialize)]
+pub struct RunSnapshot {
+    pub level: usize,
+    #[serde(default)]
+    pub mode: GameMode,
+    pub ships: Vec<ShipSnapshot>, // Ships out in the level; players being respawned have none
+    pub boxes: Vec<BoxSnapshot>,
+    pub lasers: Vec<LaserSnapshot>,
+    #[serde(default)]
+    pub enemies: Vec<EnemySnapshot>,
+    #[serde(default)]
+    pub boss: Option<BossSnapshot>, // Only for levels with a boss
+    #[serde(default)]
+    pub waves: Option<WaveSnapshot>,
+    pub profile: PlayerProfile,
+    pub timer: Option<f32>,
+}
+
+// The parts of the world making up a run in progress
+#[derive(SystemParam)]
+pub struct RunWorld<'w, 's> {
+    levels: Res<'w, Levels>,
+    current_level: Res<'w, CurrentLevel>,
+    mode: Res<'w, GameMode>,
+    waves: Option<Res<'w, WaveSpawner>>,
+    profile: Res<'w, PlayerProfile>,
+    timer: Res<'w, GameTimer>,
+    ships: Query<'w, 's, (&'static Ship, &'static Transform)>,
+    boxes: Query<'w, 's, (&'static Transform, &'static BoxDirection, ObstacleComponents), With<BoxEntity>>,
+    lasers: Query<'w, 's, (&'static Transform, &'static Laser)>,
+    enemies: Query<'w, 's, (&'static Transform, &'static Enemy)>,
+    weak_points: Query<'w, 's, &'static WeakPoint>,
+}
+
+impl RunWorld<'_, '_> {
+    // Captures the run, or `None` if there are no ships (i.e. the run is over); time attack runs and versus
+    // rounds are never saved, as pausing the clock between sessions would make them meaningless
+    fn snapshot(&self) -> Option<RunSnapshot> {
+        if !is_saved_mode(*self.mode) {
+            return None;
+        }
+        if self.ships.is_empty() {
+            return None;
+        }
+        Some(RunSnapshot {
+            level: self.current_level.0,
+            mode: *self.mode,
+            ships: self
+                .ships
+                .iter()
+                .map(|(ship, transform)| ShipSnapshot {
+                    player: ship.0,
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                })
+                .collect(),
+            boxes: self
+                .boxes
+                .iter()
+                .map(|(transform, direction, behaviors)| BoxSnapshot {
+                    translation: transform.translation,
+                    direction: direction.0,
+                    behaviors: obstacle_behaviors(behaviors),
+                })
+                .collect(),
+            lasers: self
+                .lasers
+                .iter()
+                .map(|(transform, laser)| LaserSnapshot {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    laser_type: laser.laser_type,
+                    faction: laser.faction,
+                })
+                .collect(),
+            enemies: self
+                .enemies
+                .iter()
+                .map(|(transform, enemy)| EnemySnapshot {
+                    spawn: enemy.spawn.clone(),
+                    translation: transform.translation.truncate(),
+                    health: enemy.health,
+                    ai: Some(enemy.ai()),
+                })
+                .collect(),
+            boss: self.levels.0[self.current_level.0].boss.as_ref().map(|_| {
+                // The boss and its weak points are gone once its defeat has played out
+                let mut weak_points: Vec<&WeakPoint> = self.weak_points.iter().collect();
+                weak_points.sort_by_key(|point| point.index);
+                BossSnapshot {
+                    defeated: weak_points.is_empty(),
+                    weak_points: weak_points.iter().map(|point| point.health).collect(),
+                }
+            }),
+            waves: self.waves.as_ref().map(|waves| waves.snapshot()),
+            profile: self.profile.clone(),
+            timer: self.timer.0,
+        })
+    }
+
+    fn save(&self) {
+        if let Some(snapshot) = self.snapshot() {
+            storage::save(RUN_FILE, &snapshot);
+        }
+    }
+}
+
+// True if runs in `mode` are saved when left, so starting one means giving up any saved run
+pub fn is_saved_mode(mode: GameMode) -> bool {
+    !matches!(mode, GameMode::TimeAttack | GameMode::Versus)
+}
+
+// True if there is a saved run to continue
+pub fn has_saved_run() -> bool {
+    storage::data_dir().join(RUN_FILE).exists()
+}
+
+// Loads the saved run, if any
+pub fn load_saved_run() -> Option<RunSnapshot> {
+    storage::load(RUN_FILE)
+}
+
+// System to save the run when quitting to the main menu
+pub fn save_run(run: RunWorld) {
+    run.save();
+}
+
+// System to save the run when the window is closed mid-level
+pub fn save_run_on_close(mut close_requests: EventReader<WindowCloseRequested>, run: RunWorld) {
+    if close_requests.iter().count() > 0 {
+        run.save();
+    }
+}
+
+// System to delete the save file once it is continued; also called when the player agrees to start a new run
+// in its place
+pub fn discard_saved_run() {
+    storage::remove(RUN_FILE);
+}
+
+// System to swap the freshly set up level for the saved run; the game starts paused
+pub fn restore_run(
+    mut commands: Commands,
+    snapshot: Res<RunSnapshot>,
+    asset_server: Res<AssetServer>,
+    animations: Res<Animations>,
+    config: Res<GameConfig>,
+    ships: Query<Entity, With<Ship>>,
+    boxes: Query<Entity, With<BoxEntity>>,
+    enemies: Query<Entity, With<Enemy>>,
+    bosses: Query<Entity, With<Boss>>,
+    mut weak_points: Query<&mut WeakPoint>,
+    mut end_points: Query<(Entity, &mut Sprite), (With<EndPoint>, With<Locked>)>,
+    waves: Option<ResMut<WaveSpawner>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    // The fresh level has ships for the players of the last run, which may not be the saved ones
+    for entity in ships.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for ship in snapshot.ships.iter() {
+        spawn_ship(&mut commands, &asset_server, &animations, ship.translation, ship.rotation, ship.player);
+    }
+
+    for entity in boxes.iter() {
+        commands.entity(entity).despawn();
+    }
+    let box_handle = asset_server.load("box.png");
+    for saved_box in snapshot.boxes.iter() {
+        let entity = spawn_box(&mut commands, box_handle.clone(), saved_box.translation, saved_box.direction);
+        for behavior in saved_box.behaviors.iter() {
+            behavior.insert(&mut commands.entity(entity));
+        }
+    }
+
+    for laser in snapshot.lasers.iter() {
+        spawn_laser(&mut commands, &asset_server, laser.translation, laser.rotation, laser.laser_type, laser.faction);
+    }
+
+    for entity in enemies.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for enemy in snapshot.enemies.iter() {
+        spawn_enemy(&mut commands, &asset_server, &config.enemies, &enemy.spawn, enemy.translation, enemy.health, enemy.ai);
+    }
+
+    // A boss beaten before the save stays gone with the end point open, as `run_boss_defeat` left them; one
+    // saved with no health left finishes its defeat as soon as the game resumes
+    match &snapshot.boss {
+        Some(boss) if boss.defeated => {
+            for entity in bosses.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            for (end_point, mut sprite) in end_points.iter_mut() {
+                commands.entity(end_point).remove::<Locked>();
+                sprite.color = Color::RED;
+            }
+        }
+        Some(boss) => {
+            for mut point in weak_points.iter_mut() {
+                if let Some(health) = boss.weak_points.get(point.index) {
+                    point.health = *health;
+                }
+            }
+        }
+        None => {}
+    }
+
+    if let (Some(mut waves), Some(saved)) = (waves, &snapshot.waves) {
+        waves.wave = saved.wave;
+        waves.until_next = saved.until_next;
+    }
+
+    commands.insert_resource(snapshot.profile.clone());
+    commands.insert_resource(GameTimer(snapshot.timer, false));
+
+    commands.remove_resource::<RunSnapshot>();
+    next_pause_state.set(PauseState::Paused);
+}
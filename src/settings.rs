@@ -0,0 +1,285 @@
+// settings.rs
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use crate::difficulty::Difficulty;
+use crate::display::ScaleMode;
+use crate::ui::{spawn_button, spawn_menu_root, spawn_title, ButtonActivated, MenuInput, FONT_PATH};
+
+// Plugin for the player settings and the settings menu shared by every other menu
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_state::<SettingsMenuState>()
+            .add_systems(OnEnter(SettingsMenuState::Open), spawn_settings_menu)
+            .add_systems(OnExit(SettingsMenuState::Open), despawn_settings_menu)
+            .add_systems(
+                Update,
+                (
+                    handle_settings_buttons.run_if(in_state(SettingsMenuState::Open)), // React to settings menu input
+                    update_settings_labels.run_if(resource_changed::<Settings>()),     // Keep button labels in sync
+                    apply_display_settings.run_if(resource_changed::<Settings>()),     // Push display settings to the window
+                ),
+            );
+    }
+}
+
+// Player-adjustable settings
+#[derive(Resource)]
+pub struct Settings {
+    pub difficulty: Difficulty,    // Takes effect from the next run
+    pub adaptive_difficulty: bool, // Waves follow how the player is doing
+    pub ghost: bool,               // Race against a replay of the level's fastest run
+    pub players: usize,            // Ships in the next run, picked on the title screen
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub scale_mode: ScaleMode,
+    pub master_volume: f32, // 0.0 to 1.0, scales both channels below
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub screen_shake: bool, // Camera effects on impacts, each can be turned off for comfort
+    pub hit_stop: bool,
+    pub screen_flash: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            difficulty: Difficulty::default(),
+            adaptive_difficulty: false,
+            ghost: true,
+            players: 1,
+            fullscreen: false,
+            vsync: true,
+            scale_mode: ScaleMode::default(),
+            master_volume: 1.0,
+            music_volume: 0.6,
+            sfx_volume: 0.8,
+            screen_shake: true,
+            hit_stop: true,
+            screen_flash: true,
+        }
+    }
+}
+
+impl Settings {
+    // Effective music volume after the master volume
+    pub fn music_level(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    // Effective sound effect volume after the master volume
+    pub fn sfx_level(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+}
+
+// Color of the section headings in the settings menu
+const SECTION_COLOR: Color = Color::rgb(0.6, 0.6, 0.75);
+
+// Volume change per button press; volumes wrap back to zero past the maximum
+const VOLUME_STEP: f32 = 0.1;
+
+fn step_volume(volume: &mut f32) {
+    let next = ((*volume + VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+    *volume = if next > 1.0 + f32::EPSILON { 0.0 } else { next };
+}
+
+// Whether the settings menu is currently shown (on top of whichever menu opened it)
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum SettingsMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+// Root node of the settings menu
+#[derive(Component)]
+pub struct SettingsMenu;
+
+// Buttons of the settings menu
+#[derive(Component, Clone, Copy)]
+pub enum SettingsButton {
+    Difficulty,
+    AdaptiveDifficulty,
+    Ghost,
+    Fullscreen,
+    VSync,
+    Scaling,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    ScreenShake,
+    HitStop,
+    ScreenFlash,
+    Back,
+}
+
+impl SettingsButton {
+    // The menu's sections, laid out two to a column so every button fits the logical height; Back goes below
+    const SECTIONS: [(&'static str, &'static [SettingsButton]); 4] = [
+        (
+            "Gameplay",
+            &[SettingsButton::Difficulty, SettingsButton::AdaptiveDifficulty, SettingsButton::Ghost],
+        ),
+        ("Video", &[SettingsButton::Fullscreen, SettingsButton::VSync, SettingsButton::Scaling]),
+        (
+            "Audio",
+            &[SettingsButton::MasterVolume, SettingsButton::MusicVolume, SettingsButton::SfxVolume],
+        ),
+        (
+            "Effects",
+            &[SettingsButton::ScreenShake, SettingsButton::HitStop, SettingsButton::ScreenFlash],
+        ),
+    ];
+
+    fn label(self, settings: &Settings) -> String {
+        match self {
+            SettingsButton::Difficulty => format!("Difficulty: {:?}", settings.difficulty),
+            SettingsButton::AdaptiveDifficulty => format!("Adaptive Difficulty: {}", on_off(settings.adaptive_difficulty)),
+            SettingsButton::Ghost => format!("Ghost: {}", on_off(settings.ghost)),
+            SettingsButton::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            SettingsButton::VSync => format!("VSync: {}", on_off(settings.vsync)),
+            SettingsButton::Scaling => format!("Scaling: {:?}", settings.scale_mode),
+            SettingsButton::MasterVolume => format!("Master Volume: {}", percent(settings.master_volume)),
+            SettingsButton::MusicVolume => format!("Music Volume: {}", percent(settings.music_volume)),
+            SettingsButton::SfxVolume => format!("Effects Volume: {}", percent(settings.sfx_volume)),
+            SettingsButton::ScreenShake => format!("Screen Shake: {}", on_off(settings.screen_shake)),
+            SettingsButton::HitStop => format!("Hit-Stop: {}", on_off(settings.hit_stop)),
+            SettingsButton::ScreenFlash => format!("Screen Flash: {}", on_off(settings.screen_flash)),
+            SettingsButton::Back => "Back".to_string(),
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn percent(volume: f32) -> String {
+    format!("{}%", (volume * 100.0).round())
+}
+
+// System to build the settings menu: its sections in two columns, numbered for navigation top to bottom and
+// then left to right
+pub fn spawn_settings_menu(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    let font = asset_server.load(FONT_PATH);
+    let heading_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.0,
+        color: SECTION_COLOR,
+    };
+    let root = spawn_menu_root(&mut commands, Color::rgba(0.0, 0.0, 0.0, 0.85));
+    commands.entity(root).insert(SettingsMenu).with_children(|parent| {
+        spawn_title(parent, &font, "Settings");
+        let mut order = 0;
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(40.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|columns| {
+                for sections in SettingsButton::SECTIONS.chunks(2) {
+                    columns
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                row_gap: Val::Px(12.0),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .with_children(|column| {
+                            for (heading, buttons) in sections {
+                                column.spawn(TextBundle::from_section(*heading, heading_style.clone()));
+                                for button in buttons.iter() {
+                                    spawn_button(column, &font, &button.label(&settings), order, *button);
+                                    order += 1;
+                                }
+                            }
+                        });
+                }
+            });
+        spawn_button(parent, &font, "Back", order, SettingsButton::Back);
+    });
+}
+
+// System to remove the settings menu
+pub fn despawn_settings_menu(mut commands: Commands, query: Query<Entity, With<SettingsMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// System to apply settings menu button presses
+pub fn handle_settings_buttons(
+    mut activated: EventReader<ButtonActivated>,
+    buttons: Query<&SettingsButton>,
+    menu_input: MenuInput,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<SettingsMenuState>>,
+) {
+    if menu_input.back() {
+        next_state.set(SettingsMenuState::Closed);
+    }
+
+    for event in activated.iter() {
+        if let Ok(button) = buttons.get(event.0) {
+            match button {
+                SettingsButton::Difficulty => settings.difficulty = settings.difficulty.next(),
+                SettingsButton::AdaptiveDifficulty => settings.adaptive_difficulty = !settings.adaptive_difficulty,
+                SettingsButton::Ghost => settings.ghost = !settings.ghost,
+                SettingsButton::Fullscreen => settings.fullscreen = !settings.fullscreen,
+                SettingsButton::VSync => settings.vsync = !settings.vsync,
+                SettingsButton::Scaling => settings.scale_mode = settings.scale_mode.next(),
+                SettingsButton::MasterVolume => step_volume(&mut settings.master_volume),
+                SettingsButton::MusicVolume => step_volume(&mut settings.music_volume),
+                SettingsButton::SfxVolume => step_volume(&mut settings.sfx_volume),
+                SettingsButton::ScreenShake => settings.screen_shake = !settings.screen_shake,
+                SettingsButton::HitStop => settings.hit_stop = !settings.hit_stop,
+                SettingsButton::ScreenFlash => settings.screen_flash = !settings.screen_flash,
+                SettingsButton::Back => next_state.set(SettingsMenuState::Closed),
+            }
+        }
+    }
+}
+
+// System to refresh the settings button labels after a change
+pub fn update_settings_labels(
+    settings: Res<Settings>,
+    buttons: Query<(&SettingsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in buttons.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = button.label(&settings);
+            }
+        }
+    }
+}
+
+// System to apply display settings to the primary window
+pub fn apply_display_settings(settings: Res<Settings>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
@@ -0,0 +1,141 @@
+// starfield.rs
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::component::LevelEntity;
+use crate::level::{CurrentLevel, Levels};
+use crate::state::AppState;
+use crate::system::setup;
+
+// Background layers from far to near: how much of the camera's movement each one follows,
+// its depth and the brightness and size range of its stars
+const LAYERS: [StarLayerDef; 3] = [
+    StarLayerDef { parallax: 0.9, z: -30.0, brightness: 0.35, size: (1.0, 1.5) },
+    StarLayerDef { parallax: 0.7, z: -20.0, brightness: 0.6, size: (1.0, 2.0) },
+    StarLayerDef { parallax: 0.4, z: -10.0, brightness: 0.9, size: (1.5, 3.0) },
+];
+
+// Extra room around the world covered by stars, so the view never runs out of them
+const STARFIELD_MARGIN: f32 = 800.0;
+
+// Share of stars that twinkle, and their twinkle speed range in cycles per second
+const TWINKLE_CHANCE: f64 = 0.3;
+const TWINKLE_SPEED: (f32, f32) = (0.3, 1.5);
+
+// Plugin for the level's scrolling starfield background
+pub struct StarfieldPlugin;
+
+impl Plugin for StarfieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.03)))
+            .add_systems(OnEnter(AppState::InGame), spawn_starfield.after(setup))
+            .add_systems(Update, twinkle_stars.run_if(in_state(AppState::InGame)))
+            // Once the camera has settled for the frame, before transforms are propagated
+            .add_systems(
+                PostUpdate,
+                scroll_starfield
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+struct StarLayerDef {
+    parallax: f32,
+    z: f32,
+    brightness: f32,
+    size: (f32, f32),
+}
+
+// Layer of stars, dragged along with `parallax` times the camera's movement
+#[derive(Component)]
+pub struct StarLayer {
+    pub parallax: f32,
+}
+
+// Star whose brightness pulses around `alpha`
+#[derive(Component)]
+pub struct Twinkle {
+    alpha: f32,
+    phase: f32,
+    speed: f32,
+}
+
+// System to scatter the current level's stars over its layers, the same way every time the level is played
+pub fn spawn_starfield(mut commands: Commands, levels: Res<Levels>, current_level: Res<CurrentLevel>) {
+    let level = &levels.0[current_level.0];
+    let mut rng = StdRng::seed_from_u64(level.seed());
+
+    for layer in LAYERS.iter() {
+        // Far layers hardly move, so they only need to cover a little more than the view
+        let half_size = level.bounds / 2.0 * (1.0 - layer.parallax) + STARFIELD_MARGIN;
+        let area = half_size.x * half_size.y * 4.0;
+        let count = (area / 10_000.0 * level.star_density / LAYERS.len() as f32) as usize;
+
+        commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, layer.z)),
+                StarLayer { parallax: layer.parallax },
+                LevelEntity,
+            ))
+            .with_children(|stars| {
+                for _ in 0..count {
+                    let position = Vec3::new(
+                        rng.gen_range(-half_size.x..half_size.x),
+                        rng.gen_range(-half_size.y..half_size.y),
+                        0.0,
+                    );
+                    let size = rng.gen_range(layer.size.0..=layer.size.1);
+                    let alpha = layer.brightness * rng.gen_range(0.6..=1.0);
+                    // Mostly white, with a few blue and yellow stars
+                    let tint = match rng.gen_range(0..10) {
+                        0 => Color::rgba(0.7, 0.8, 1.0, alpha),
+                        1 => Color::rgba(1.0, 0.95, 0.7, alpha),
+                        _ => Color::rgba(1.0, 1.0, 1.0, alpha),
+                    };
+
+                    let mut star = stars.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: tint,
+                            custom_size: Some(Vec2::splat(size)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(position),
+                        ..Default::default()
+                    });
+                    if rng.gen_bool(TWINKLE_CHANCE) {
+                        star.insert(Twinkle {
+                            alpha,
+                            phase: rng.gen_range(0.0..TAU),
+                            speed: rng.gen_range(TWINKLE_SPEED.0..=TWINKLE_SPEED.1),
+                        });
+                    }
+                }
+            });
+    }
+}
+
+// System to move each layer along with its share of the camera's position
+pub fn scroll_starfield(
+    cameras: Query<&Transform, (With<Camera2d>, Without<StarLayer>)>,
+    mut layers: Query<(&StarLayer, &mut Transform)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    for (layer, mut transform) in layers.iter_mut() {
+        let offset = camera.translation.truncate() * layer.parallax;
+        transform.translation = offset.extend(transform.translation.z);
+    }
+}
+
+// System to pulse the brightness of twinkling stars
+pub fn twinkle_stars(time: Res<Time>, mut stars: Query<(&Twinkle, &mut Sprite)>) {
+    let t = time.elapsed_seconds();
+    for (twinkle, mut sprite) in stars.iter_mut() {
+        let pulse = 0.65 + 0.35 * (t * twinkle.speed * TAU + twinkle.phase).sin();
+        sprite.color.set_a(twinkle.alpha * pulse);
+    }
+}
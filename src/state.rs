@@ -0,0 +1,41 @@
+// state.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Top-level application state
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,    // Title screen
+    LevelSelect, // Level select screen
+    InGame,      // A level is loaded and being played (possibly paused)
+    Results,     // Game-over or level-complete screen with the leaderboards
+    Restarting,  // Transient state used to tear the level down and build it again
+}
+
+// Pause state, layered on top of `AppState::InGame`
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// How levels are played, picked from the title screen
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Campaign,   // Fly to the end point, one level after another
+    Survival,   // No end point; last as long as possible against endless waves
+    TimeAttack, // One level against the clock, with checkpoint splits, par times and medals
+    Versus,     // Two players shoot it out in an arena until one is left or the round timer runs out
+}
+
+// System set for every gameplay system; only runs while in game and not paused
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
+
+// System to go straight back into the game after a restart was requested
+pub fn finish_restart(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
@@ -0,0 +1,51 @@
+// storage.rs
+use std::fs;
+use std::path::PathBuf;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use bevy::log::warn;
+
+// Environment variable overriding where save data is kept
+const DATA_DIR_VAR: &str = "STARSHIP_DATA_DIR";
+
+// Directory holding save data: `$STARSHIP_DATA_DIR` if set, otherwise the platform data dir
+pub fn data_dir() -> PathBuf {
+    match std::env::var_os(DATA_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("starship_game"),
+    }
+}
+
+// Reads a RON file from the data dir; missing or unreadable files yield `None`
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = data_dir().join(file_name);
+    let contents = fs::read_to_string(&path).ok()?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("Ignoring corrupt save file {}: {}", path.display(), error);
+            None
+        }
+    }
+}
+
+// Writes a value as RON into the data dir, creating the directory if needed
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let dir = data_dir();
+    let result = fs::create_dir_all(&dir)
+        .map_err(|error| error.to_string())
+        .and_then(|_| {
+            ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|error| error.to_string())
+        })
+        .and_then(|contents| fs::write(dir.join(file_name), contents).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        warn!("Failed to write {}: {}", dir.join(file_name).display(), error);
+    }
+}
+
+// Removes a file from the data dir if it exists
+pub fn remove(file_name: &str) {
+    let _ = fs::remove_file(data_dir().join(file_name));
+}
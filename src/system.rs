@@ -1,50 +1,54 @@
 // system.rs
 use bevy::prelude::*;
 use crate::component::{
-    BoxDirection, BoxEntity, EndPoint, GameTimer, Laser, Ship, StartPoint,
-    Fireball, FireballAnimationTimer, ShipLives, FireballAtlas, Score, ScoreDisplay,
+    BoxDirection, BoxEntity, EndPoint, Faction, GameTimer, Invulnerable, Laser, Locked, Ship, StartPoint,
+    Fireball, LevelEntity, Thrust, Thruster,
 };
+use crate::boss::spawn_boss;
+use crate::enemy::spawn_enemy;
+use crate::animation::{animated_sprite, Animations};
+use crate::config::GameConfig;
+use crate::particle::{ParticleEmitter, EXHAUST};
+use crate::profile::{LifeLost, PlayerProfile, MAX_PLAYERS};
+use crate::level::{CurrentLevel, Levels, WorldBounds};
+use crate::results::RunOutcome;
+use crate::state::{AppState, GameMode};
+use crate::timeattack::Splits;
+use crate::wave::WaveSpawner;
 use rand::Rng;
-use bevy::ecs::system::ParamSet;
 
-// Marker component for the text displaying lives and timer
-#[derive(Component)]
-pub struct ShipLivesDisplay;
+// Distance between the ships of neighbouring players at the start point
+const SHIP_SPACING: f32 = 50.0;
+
+// Random spots tried for each starting box before giving up on keeping it clear of the start point
+const BOX_PLACEMENT_TRIES: usize = 100;
+
+// In versus, the players start this far either side of the middle of the arena
+const VERSUS_SPAWN_DISTANCE: f32 = 400.0;
 
 // System to set up initial entities
 pub fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    animations: Res<Animations>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+    profile: Res<PlayerProfile>,
+    config: Res<GameConfig>,
 ) {
-    // Spawn 2D camera
-    commands.spawn(Camera2dBundle::default());
-
-    // Initialize the score to zero
-    commands.insert_resource(Score(0));
-
-    // Display the initial score on the screen
-    commands.spawn(TextBundle {
-        text: Text::from_section(
-            "Score: 0",
-            TextStyle {
-                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                font_size: 30.0,
-                color: Color::WHITE,
-            },
-        ),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            right: Val::Px(10.0),
-            ..Default::default()
-        },
-        ..Default::default()
-    })
-    .insert(ScoreDisplay);
+    let level = &levels.0[current_level.0];
+    let survival = *mode == GameMode::Survival;
+    let arena = survival || *mode == GameMode::Versus; // Played for as long as it lasts, not to get anywhere
+
+    // Reset the game timer until the player moves
+    commands.insert_resource(GameTimer(None, false));
+
+    // The level's world, with a faint border along its edges
+    let bounds = WorldBounds::new(level.bounds);
+    spawn_world_border(&mut commands, &bounds);
 
     // Load textures
-    let ship_handle = asset_server.load("ship.png");
     let box_handle = asset_server.load("box.png");
 
     // Spawn start point
@@ -54,88 +58,211 @@ pub fn setup(
             ..Default::default()
         },
         transform: Transform {
-            translation: Vec3::new(-400.0, 300.0, 0.0),
+            translation: level.start,
             scale: Vec3::new(20.0, 20.0, 1.0),
             ..Default::default()
         },
         ..Default::default()
     })
-    .insert(StartPoint);
+    .insert(StartPoint)
+    .insert(LevelEntity);
+
+    // Spawn a ship for each player with lives left; their lives are kept in the player profile
+    for (player, stats) in profile.players.iter().enumerate() {
+        if stats.lives > 0 {
+            let translation = ship_start(&bounds, level.start, player, *mode);
+            spawn_ship(&mut commands, &asset_server, &animations, translation, Quat::IDENTITY, player);
+        }
+    }
 
-    // Spawn ship with 5 lives
-    commands.spawn(SpriteBundle {
-        texture: ship_handle.clone(),
-        transform: Transform {
-            translation: Vec3::new(-400.0, 300.0, 0.0),
-            scale: Vec3::new(0.1, 0.1, 1.0),
-            rotation: Quat::from_rotation_z(0.0),
+    // Spawn end point; it stays locked (and grey) while the level has a boss to defeat. Survival and versus
+    // have neither, as there is nowhere to get to
+    if !arena {
+        let mut end_point = commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: if level.boss.is_some() { Color::GRAY } else { Color::RED },
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: level.end,
+                scale: Vec3::new(20.0, 20.0, 1.0),
+                ..Default::default()
+            },
             ..Default::default()
-        },
-        ..Default::default()
-    })
-    .insert(Ship)
-    .insert(ShipLives(5)); // Initialize with 5 lives
+        });
+        end_point.insert(EndPoint).insert(LevelEntity);
+        if let Some(boss) = &level.boss {
+            end_point.insert(Locked);
+            spawn_boss(&mut commands, &asset_server, &config.boss, boss);
+        }
+    }
+
+    // Spawn boxes anywhere in the world except right on top of the ship; harder difficulties start with more
+    let mut rng = rand::thread_rng();
+    let box_count = (level.box_count as f32 * config.difficulty.preset(profile.difficulty).box_count).round() as usize;
+    for _ in 0..box_count {
+        // In a world too small for the clearance, settle for the farthest of the tries
+        let mut position = Vec3::ZERO;
+        for _ in 0..BOX_PLACEMENT_TRIES {
+            let x = rng.gen_range(-bounds.half_size.x..bounds.half_size.x);
+            let y = rng.gen_range(-bounds.half_size.y..bounds.half_size.y);
+            let candidate = Vec3::new(x, y, 0.0);
+            if candidate.distance(level.start) > position.distance(level.start) {
+                position = candidate;
+            }
+            if position.distance(level.start) > config.boxes.spawn_clearance {
+                break;
+            }
+        }
+        let direction = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0).normalize_or_zero();
+
+        spawn_box(&mut commands, box_handle.clone(), position, direction);
+    }
+
+    // Spawn the level's obstacles, with their movement behaviors
+    for obstacle in level.obstacles.iter() {
+        let direction = obstacle
+            .direction
+            .unwrap_or_else(|| Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)))
+            .normalize_or_zero();
+        let entity = spawn_box(&mut commands, box_handle.clone(), obstacle.position.extend(0.0), direction.extend(0.0));
+        for behavior in obstacle.behaviors.iter() {
+            behavior.insert(&mut commands.entity(entity));
+        }
+    }
 
-    // Spawn end point
+    // Spawn the level's enemies; in survival, all enemies come with the waves, and versus has none
+    if !arena {
+        for enemy in level.enemies.iter() {
+            let health = config.enemies.kind(enemy.kind).health;
+            spawn_enemy(&mut commands, &asset_server, &config.enemies, enemy, enemy.position, health, None);
+        }
+    }
+
+    // Start the level's waves, if it has any; versus keeps to the boxes already out there
+    match (&level.waves, *mode) {
+        (_, GameMode::Versus) => commands.remove_resource::<WaveSpawner>(),
+        (Some(waves), _) => commands.insert_resource(WaveSpawner::new(waves.clone())),
+        (None, GameMode::Survival) => commands.insert_resource(WaveSpawner::new(Default::default())),
+        (None, _) => commands.remove_resource::<WaveSpawner>(),
+    }
+
+    commands.insert_resource(bounds);
+}
+
+// Spawns thin lines along the edges of the world
+fn spawn_world_border(commands: &mut Commands, bounds: &WorldBounds) {
+    let half = bounds.half_size;
+    let thickness = 4.0;
+    let edges = [
+        (Vec2::new(0.0, half.y), Vec2::new(half.x * 2.0, thickness)),
+        (Vec2::new(0.0, -half.y), Vec2::new(half.x * 2.0, thickness)),
+        (Vec2::new(half.x, 0.0), Vec2::new(thickness, half.y * 2.0)),
+        (Vec2::new(-half.x, 0.0), Vec2::new(thickness, half.y * 2.0)),
+    ];
+    for (center, size) in edges {
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.4, 0.6, 1.0, 0.4),
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(-1.0)),
+            ..Default::default()
+        })
+        .insert(LevelEntity);
+    }
+}
+
+// Where a player's ship starts; in co-op the ships line up below the start point, and in versus they
+// face off from either side of the middle of the arena
+pub fn ship_start(bounds: &WorldBounds, start: Vec3, player: usize, mode: GameMode) -> Vec3 {
+    if mode == GameMode::Versus {
+        let side = if player % 2 == 0 { -1.0 } else { 1.0 };
+        return bounds.clamp(Vec3::X * side * VERSUS_SPAWN_DISTANCE);
+    }
+    bounds.clamp(start - Vec3::Y * SHIP_SPACING * player as f32)
+}
+
+// Position of the ship closest to `position`, if any ship is out there
+pub fn nearest_ship<'a>(ships: impl Iterator<Item = &'a Transform>, position: Vec2) -> Option<Vec2> {
+    ships
+        .map(|ship| ship.translation.truncate())
+        .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)))
+}
+
+// Spawns a player's ship, tinted in their color, with its engine flame behind it
+pub fn spawn_ship(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    animations: &Animations,
+    translation: Vec3,
+    rotation: Quat,
+    player: usize,
+) {
     commands.spawn(SpriteBundle {
+        texture: asset_server.load("ship.png"),
         sprite: Sprite {
-            color: Color::RED,
+            color: Ship(player).color(),
             ..Default::default()
         },
         transform: Transform {
-            translation: Vec3::new(400.0, -300.0, 0.0),
-            scale: Vec3::new(20.0, 20.0, 1.0),
-            ..Default::default()
+            translation,
+            scale: Vec3::new(0.1, 0.1, 1.0), // Consistent size for spawned and respawned ships
+            rotation,
         },
         ..Default::default()
     })
-    .insert(EndPoint);
-
-    // Display ship lives and timer
-    commands.spawn(TextBundle {
-        text: Text::from_section(
-            "Lives: 5\nTime: 0.00 seconds",
-            TextStyle {
-                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                font_size: 30.0,
-                color: Color::WHITE,
+    .insert(Ship(player))
+    .insert(Thrust::default())
+    .insert(LevelEntity)
+    .with_children(|ship| {
+        // Child transforms are in the ship's scaled space; this puts a 16x32 flame at its tail
+        ship.spawn(animated_sprite(
+            &animations.thruster,
+            "burn",
+            Transform {
+                translation: Vec3::new(0.0, -560.0, -0.5),
+                scale: Vec3::new(10.0, 10.0, 1.0),
+                ..Default::default()
             },
-        ),
-        style: Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
+        ))
+        .insert(Visibility::Hidden)
+        .insert(ParticleEmitter::new(EXHAUST, Vec2::NEG_Y))
+        .insert(Thruster);
+    });
+}
+
+// Spawns an exploding fireball
+pub fn spawn_fireball(commands: &mut Commands, animations: &Animations, translation: Vec3) {
+    commands.spawn(animated_sprite(
+        &animations.fireball,
+        "explode",
+        Transform {
+            translation,
+            scale: Vec3::new(0.5, 0.5, 1.0),
             ..Default::default()
         },
-        ..Default::default()
-    })
-    .insert(ShipLivesDisplay);
-
-    // Spawn boxes
-    for _ in 0..10 {
-        let mut rng = rand::thread_rng();
-        let x = rng.gen_range(-400.0..400.0);
-        let y = rng.gen_range(-300.0..300.0);
-        let direction = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0).normalize_or_zero();
+    ))
+    .insert(Fireball)
+    .insert(LevelEntity);
+}
 
-        commands.spawn(SpriteBundle {
-            texture: box_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(x, y, 0.0),
-                scale: Vec3::new(0.2, 0.2, 1.0),
-                ..Default::default()
-            },
+// Spawns a box drifting along `direction`
+pub fn spawn_box(commands: &mut Commands, texture: Handle<Image>, translation: Vec3, direction: Vec3) -> Entity {
+    commands.spawn(SpriteBundle {
+        texture,
+        transform: Transform {
+            translation,
+            scale: Vec3::new(0.2, 0.2, 1.0),
             ..Default::default()
-        })
-        .insert(BoxEntity)
-        .insert(BoxDirection(direction));
-    }
-
-    // Set up fireball sprite atlas
-    let fireball_texture_handle = asset_server.load("fireball.png");
-    let fireball_atlas = TextureAtlas::from_grid(fireball_texture_handle, Vec2::new(64.0, 64.0), 4, 4, None, None);
-    let fireball_atlas_handle = texture_atlases.add(fireball_atlas);
-    commands.insert_resource(FireballAtlas(fireball_atlas_handle));
+        },
+        ..Default::default()
+    })
+    .insert(BoxEntity)
+    .insert(BoxDirection(direction))
+    .insert(LevelEntity)
+    .id()
 }
 
 // System to handle box movement and stop movement when the game ends
@@ -143,48 +270,23 @@ pub fn box_movement(
     time: Res<Time>,
     mut box_query: Query<(&mut Transform, &BoxDirection), With<BoxEntity>>,
     game_timer: Res<GameTimer>, // Check the game state using the GameTimer resource
+    bounds: Res<WorldBounds>,
+    profile: Res<PlayerProfile>,
+    config: Res<GameConfig>,
 ) {
     // If the game is not stopped, allow the boxes to move
     if !game_timer.1 {
-        let speed = 100.0;
+        let speed = config.boxes.speed * config.difficulty.preset(profile.difficulty).box_speed;
 
         for (mut box_transform, direction) in box_query.iter_mut() {
             box_transform.translation += direction.0 * speed * time.delta_seconds();
 
-            // Wrap around screen edges to prevent blinking
-            if box_transform.translation.x > 400.0 || box_transform.translation.x < -400.0 {
-                box_transform.translation.x = -box_transform.translation.x;
+            // Wrap around the world edges
+            if box_transform.translation.x.abs() > bounds.half_size.x {
+                box_transform.translation.x = -box_transform.translation.x.clamp(-bounds.half_size.x, bounds.half_size.x);
             }
-            if box_transform.translation.y > 300.0 || box_transform.translation.y < -300.0 {
-                box_transform.translation.y = -box_transform.translation.y;
-            }
-        }
-    }
-}
-
-// System to handle box and ship collision, and spawn fireballs when they collide
-pub fn box_ship_collision(
-    mut commands: Commands,
-    box_query: Query<&Transform, With<BoxEntity>>,
-    ship_query: Query<&Transform, With<Ship>>,
-    fireball_atlas: Res<FireballAtlas>,
-) {
-    if let Ok(ship_transform) = ship_query.get_single() {
-        for box_transform in box_query.iter() {
-            let collision_distance = 30.0;
-            if box_transform.translation.distance(ship_transform.translation) < collision_distance {
-                // Release fireball when a box collides with the ship
-                commands.spawn(SpriteSheetBundle {
-                    texture_atlas: fireball_atlas.0.clone(),
-                    transform: Transform {
-                        translation: ship_transform.translation,
-                        scale: Vec3::new(0.5, 0.5, 1.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(Fireball)
-                .insert(FireballAnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)));
+            if box_transform.translation.y.abs() > bounds.half_size.y {
+                box_transform.translation.y = -box_transform.translation.y.clamp(-bounds.half_size.y, bounds.half_size.y);
             }
         }
     }
@@ -195,89 +297,110 @@ pub fn move_laser(
     time: Res<Time>,
     mut commands: Commands,
     mut laser_query: Query<(Entity, &mut Transform), With<Laser>>,
+    bounds: Res<WorldBounds>,
+    profile: Res<PlayerProfile>,
+    config: Res<GameConfig>,
 ) {
-    let laser_speed = 500.0; // Adjust the speed of the laser
+    let laser_speed = config.lasers.speed * config.difficulty.preset(profile.difficulty).laser_speed;
     for (laser_entity, mut transform) in laser_query.iter_mut() {
         // Move the laser in the direction it was facing when it was spawned
         let laser_direction = transform.rotation * Vec3::Y; // Move based on its current rotation
         transform.translation += laser_direction * laser_speed * time.delta_seconds();
 
-        // Despawn the laser if it goes off-screen
-        if transform.translation.y > 800.0 || transform.translation.y < -800.0 || transform.translation.x > 1200.0 || transform.translation.x < -1200.0 {
+        // Despawn the laser once it has left the world
+        if !bounds.contains(transform.translation, config.lasers.margin) {
             commands.entity(laser_entity).despawn();
         }
     }
 }
 
-// System to detect laser and box collision and update score accordingly
+// Event fired when a box is destroyed, with where it was
+#[derive(Event)]
+pub struct BoxDestroyed {
+    pub translation: Vec3,
+}
+
+// Event fired when a laser hits something, with where and which way it was travelling
+#[derive(Event)]
+pub struct LaserImpact {
+    pub translation: Vec3,
+    pub direction: Vec2,
+}
+
+// Removes a laser that hit something, leaving a spark where it hit
+pub fn laser_hit(
+    commands: &mut Commands,
+    animations: &Animations,
+    laser_impact: &mut EventWriter<LaserImpact>,
+    laser_entity: Entity,
+    laser_transform: &Transform,
+) {
+    commands.entity(laser_entity).despawn();
+    commands.spawn(animated_sprite(
+        &animations.laser_impact,
+        "spark",
+        Transform::from_translation(laser_transform.translation + Vec3::Z),
+    ))
+    .insert(LevelEntity);
+    laser_impact.send(LaserImpact {
+        translation: laser_transform.translation,
+        direction: (laser_transform.rotation * Vec3::Y).truncate(),
+    });
+}
+
+// System to detect laser and box collision and update score accordingly; any laser breaks boxes,
+// but only players' lasers score, for whoever fired them
 pub fn detect_laser_collision(
     mut commands: Commands,
-    laser_query: Query<(Entity, &Transform), With<Laser>>,
+    laser_query: Query<(Entity, &Transform, &Laser)>,
     mut box_query: Query<(Entity, &Transform), With<BoxEntity>>,
-    mut score_display_query: Query<&mut Text, With<ScoreDisplay>>, // Query for the score display
-    mut score: ResMut<Score>, // Access to the Score resource
+    mut profile: ResMut<PlayerProfile>, // Access to the player's score
+    mut box_destroyed: EventWriter<BoxDestroyed>,
+    mut laser_impact: EventWriter<LaserImpact>,
+    animations: Res<Animations>,
+    config: Res<GameConfig>,
 ) {
-    let mut boxes_destroyed = 0; // Track the number of boxes destroyed in this iteration
-
-    for (laser_entity, laser_transform) in laser_query.iter() {
+    for (laser_entity, laser_transform, laser) in laser_query.iter() {
         for (box_entity, box_transform) in box_query.iter_mut() {
-            let collision_distance = 30.0;
-            if laser_transform.translation.distance(box_transform.translation) < collision_distance {
+            if laser_transform.translation.distance(box_transform.translation) < config.collision_radius {
                 // Despawn both laser and box
-                commands.entity(laser_entity).despawn();
+                laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
                 commands.entity(box_entity).despawn();
-                boxes_destroyed += 1; // Increment the count of destroyed boxes
+                box_destroyed.send(BoxDestroyed { translation: box_transform.translation });
+                if let Faction::Player(player) = laser.faction {
+                    // Update the shooter's score for the destroyed box
+                    if let Some(stats) = profile.players.get_mut(player) {
+                        stats.score += 1;
+                        stats.kills += 1;
+                    }
+                }
                 break;
             }
         }
     }
-
-    if boxes_destroyed > 0 {
-        // Update the score based on the number of boxes destroyed
-        score.0 += boxes_destroyed;
-
-        // Update the score display text
-        if let Ok(mut score_text) = score_display_query.get_single_mut() {
-            score_text.sections[0].value = format!("Score: {}", score.0);
-        }
-    }
 }
 
-// System to animate fireballs
-pub fn animate_fireball(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut FireballAnimationTimer, &mut TextureAtlasSprite), With<Fireball>>,
+// System to show the engine flame and exhaust only while the ship is being steered
+pub fn update_thrusters(
+    ships: Query<&Thrust, With<Ship>>,
+    mut thrusters: Query<(&Parent, &mut Visibility, &mut ParticleEmitter), With<Thruster>>,
 ) {
-    for (entity, mut timer, mut sprite) in query.iter_mut() {
-        timer.0.tick(time.delta()); // Use the timer field
-        if timer.0.finished() { // Check if the timer is finished
-            sprite.index += 1;
-            if sprite.index >= 16 {
-                commands.entity(entity).despawn();
-            }
-        }
-        
+    for (parent, mut visibility, mut exhaust) in thrusters.iter_mut() {
+        let thrusting = ships.get(parent.get()).is_ok_and(|thrust| thrust.0 != Vec3::ZERO);
+        *visibility = if thrusting { Visibility::Inherited } else { Visibility::Hidden };
+        exhaust.active = thrusting;
     }
 }
 
-
-// Timer update and display system
-pub fn update_timer_display(
+// Timer update system
+pub fn tick_game_timer(
     time: Res<Time>,
     mut timer: ResMut<GameTimer>,
-    mut lives_display_query: Query<&mut Text, With<ShipLivesDisplay>>, // Ensure lives and timer are displayed together
 ) {
     // Update the elapsed time only if the timer is not stopped
     if !timer.1 {
         if let Some(ref mut elapsed_time) = timer.0 {
             *elapsed_time += time.delta_seconds();
-
-            // Update the lives and timer display
-            for mut text in lives_display_query.iter_mut() {
-                let lives_text = text.sections[0].value.split('\n').next().unwrap_or("Lives: 0").to_string();
-                text.sections[0].value = format!("{}\nTime: {:.2} seconds", lives_text, *elapsed_time);
-            }
         }
     }
 }
@@ -285,137 +408,139 @@ pub fn update_timer_display(
 // System to detect starship-box collisions and handle game logic
 pub fn detect_starship_box_collision(
     mut commands: Commands,
-    mut ship_query: Query<(Entity, &Transform, &mut ShipLives), With<Ship>>,
+    ship_query: Query<(Entity, &Ship, &Transform), Without<Invulnerable>>,
     mut box_query: Query<(Entity, &Transform), With<BoxEntity>>, // Update to retrieve box entities
-    fireball_atlas: Res<FireballAtlas>,
-    asset_server: Res<AssetServer>,
-    mut text_query: ParamSet<(Query<&mut Text, With<ShipLivesDisplay>>, Query<&mut Text, With<ScoreDisplay>>)>, // Use ParamSet for disjoint queries
-    mut score: ResMut<Score>, // Access to the Score resource
-    mut game_timer: ResMut<GameTimer>, // Access to the game timer
+    animations: Res<Animations>,
+    mut profile: ResMut<PlayerProfile>, // Lives and score
+    mut life_lost: EventWriter<LifeLost>,
+    mut box_destroyed: EventWriter<BoxDestroyed>,
+    config: Res<GameConfig>,
 ) {
-    if let Ok((ship_entity, ship_transform, mut lives)) = ship_query.get_single_mut() {
+    let mut hit_boxes = Vec::new(); // Boxes already taken out by a ship this frame
+
+    for (ship_entity, ship, ship_transform) in ship_query.iter() {
         let ship_position = ship_transform.translation;
         let mut collided_boxes = 0; // Track the number of boxes collided with the starship
 
         for (box_entity, box_transform) in box_query.iter_mut() {
-            let collision_distance = 30.0;
-            if ship_position.distance(box_transform.translation) < collision_distance {
+            if hit_boxes.contains(&box_entity) {
+                continue;
+            }
+            if ship_position.distance(box_transform.translation) < config.collision_radius {
+                hit_boxes.push(box_entity);
                 collided_boxes += 1; // Increment the count of collided boxes
 
                 // Despawn the box after collision
                 commands.entity(box_entity).despawn();
+                box_destroyed.send(BoxDestroyed { translation: box_transform.translation });
 
                 // Spawn fireball at the collision point
-                commands.spawn(SpriteSheetBundle {
-                    texture_atlas: fireball_atlas.0.clone(),
-                    transform: Transform {
-                        translation: ship_position,
-                        scale: Vec3::new(0.5, 0.5, 1.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(Fireball)
-                .insert(FireballAnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)));
+                spawn_fireball(&mut commands, &animations, ship_position);
             }
         }
 
         if collided_boxes > 0 {
-            // Reduce ship lives
-            lives.0 -= 1;
+            // Increase the pilot's score based on the number of boxes destroyed
+            profile.players[ship.0].score += collided_boxes;
 
-            // Update lives display
-            if let Ok(mut lives_text) = text_query.p0().get_single_mut() {
-                lives_text.sections[0].value = format!("Lives: {}\nTime: 0.00 seconds", lives.0);
-            }
+            destroy_ship(&mut commands, &mut profile, &mut life_lost, ship_entity, ship.0);
+        }
+    }
+}
 
-            // Increase score based on the number of boxes destroyed
-            score.0 += collided_boxes; // Update the score based on the number of boxes collided
-            if let Ok(mut score_text) = text_query.p1().get_single_mut() {
-                score_text.sections[0].value = format!("Score: {}", score.0);
-            }
+// Takes a life from `player` and despawns their ship; it is respawned or the game ends when the lost life is handled
+pub fn destroy_ship(
+    commands: &mut Commands,
+    profile: &mut PlayerProfile,
+    life_lost: &mut EventWriter<LifeLost>,
+    ship_entity: Entity,
+    player: usize,
+) {
+    let stats = &mut profile.players[player];
+    stats.lives = stats.lives.saturating_sub(1);
+    life_lost.send(LifeLost { player, lives: stats.lives });
+    commands.entity(ship_entity).despawn_recursive();
+}
 
-            // Check if lives are zero to end the game
-            if lives.0 <= 0 {
-                commands.spawn(TextBundle {
-                    text: Text::from_section(
-                        "Game Over!",
-                        TextStyle {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 50.0,
-                            color: Color::RED,
-                        },
-                    ),
-                    style: Style {
-                        position_type: PositionType::Absolute,
-                        top: Val::Px(250.0),
-                        left: Val::Px(200.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                });
-
-                // Stop the game timer
-                game_timer.1 = true; // Set the timer's boolean to stop tracking time
-            }
+// System to respawn ships after lost lives, or end the game when no player has any left; versus rounds
+// are ended by `versus::end_round` instead
+pub fn respawn_or_game_over(
+    mut commands: Commands,
+    mut life_lost: EventReader<LifeLost>,
+    asset_server: Res<AssetServer>,
+    animations: Res<Animations>,
+    start_point_query: Query<&Transform, With<StartPoint>>,
+    profile: Res<PlayerProfile>,
+    mode: Res<GameMode>,
+    bounds: Res<WorldBounds>,
+    mut game_timer: ResMut<GameTimer>, // Access to the game timer
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // Only each player's latest event matters if they lost several lives at once
+    let mut lost = [None; MAX_PLAYERS];
+    for event in life_lost.iter() {
+        lost[event.player] = Some(event.lives);
+    }
+    if lost.iter().all(Option::is_none) {
+        return;
+    }
 
-            // Despawn the ship after collision and respawn if there are lives left
-            commands.entity(ship_entity).despawn();
-
-            if lives.0 > 0 {
-                let start_point_position = Vec3::new(-400.0, 300.0, 0.0);
-                commands.spawn(SpriteBundle {
-                    texture: asset_server.load("ship.png"),
-                    transform: Transform {
-                        translation: start_point_position,
-                        scale: Vec3::new(0.1, 0.1, 1.0), // Consistent size for respawned ship
-                        rotation: Quat::from_rotation_z(0.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                .insert(Ship)
-                .insert(ShipLives(lives.0));
-            }
+    // Check if every player is out of lives to end the game
+    if profile.all_out() && *mode != GameMode::Versus {
+        // Stop the game timer
+        game_timer.1 = true; // Set the timer's boolean to stop tracking time
+
+        // Show the game over screen
+        commands.insert_resource(RunOutcome::GameOver);
+        next_state.set(AppState::Results);
+        return;
+    }
+
+    let start_point_position = start_point_query
+        .get_single()
+        .map(|start_point| start_point.translation)
+        .unwrap_or(Vec3::ZERO);
+    for (player, lives) in lost.into_iter().enumerate() {
+        if lives.is_some_and(|lives| lives > 0) {
+            let translation = ship_start(&bounds, start_point_position, player, *mode);
+            spawn_ship(&mut commands, &asset_server, &animations, translation, Quat::IDENTITY, player);
         }
     }
 }
 
-// System to check if the ship has reached the end point
+// System to check if any ship has reached the end point
 pub fn check_end_point_reached(
     mut commands: Commands,
     mut query: Query<(Entity, &Transform), With<Ship>>,
-    end_point_query: Query<&Transform, With<EndPoint>>,
+    end_point_query: Query<&Transform, (With<EndPoint>, Without<Locked>)>,
     mut timer: ResMut<GameTimer>,
-    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+    config: Res<GameConfig>,
+    splits: Option<Res<Splits>>,
 ) {
-    if let Ok((ship_entity, ship_transform)) = query.get_single_mut() {
-        if let Ok(end_point_transform) = end_point_query.get_single() {
-            let collision_distance = 30.0;
-            if ship_transform.translation.distance(end_point_transform.translation) < collision_distance {
+    // In time attack the end point only counts once every checkpoint has been passed
+    if splits.is_some_and(|splits| !splits.route_done()) {
+        return;
+    }
+    if let Ok(end_point_transform) = end_point_query.get_single() {
+        for (ship_entity, ship_transform) in query.iter_mut() {
+            if ship_transform.translation.distance(end_point_transform.translation) < config.collision_radius {
                 // Despawn the ship and stop the game timer
-                commands.entity(ship_entity).despawn();
+                commands.entity(ship_entity).despawn_recursive();
                 timer.1 = true; // Stop the timer
 
-                // Display the "Level 01 Complete" message on the app screen
-                commands.spawn(TextBundle {
-                    text: Text::from_section(
-                        "Level 01 Complete", // Message displayed on the app screen
-                        TextStyle {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 50.0,
-                            color: Color::GREEN,
-                        },
-                    ),
-                    style: Style {
-                        position_type: PositionType::Absolute,
-                        top: Val::Px(300.0), // Adjust the position on the screen
-                        left: Val::Px(200.0),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                });
+                // Show the level complete screen
+                commands.insert_resource(RunOutcome::LevelComplete);
+                next_state.set(AppState::Results);
+                break;
             }
         }
     }
 }
+
+// System to despawn every entity belonging to the current level
+pub fn cleanup_level(mut commands: Commands, query: Query<Entity, With<LevelEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,254 @@
+// timeattack.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::component::{GameTimer, LevelEntity, Ship};
+use crate::config::GameConfig;
+use crate::level::{CurrentLevel, Levels};
+use crate::state::{AppState, GameMode, GameplaySet};
+use crate::system::setup;
+use crate::ui::FONT_PATH;
+
+// Size of a checkpoint's marker
+const CHECKPOINT_SIZE: f32 = 40.0;
+
+// Colors of the next checkpoint, the ones after it and the ones already passed
+const NEXT_CHECKPOINT: Color = Color::rgba(1.0, 0.85, 0.1, 0.9);
+const LATER_CHECKPOINT: Color = Color::rgba(1.0, 0.85, 0.1, 0.3);
+const PASSED_CHECKPOINT: Color = Color::rgba(0.5, 0.5, 0.5, 0.3);
+
+// Plugin for time attack: checkpoints along the level, split times and medals
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), start_time_attack.after(setup)).add_systems(
+            Update,
+            pass_checkpoints.run_if(resource_exists::<Splits>()).in_set(GameplaySet), // Record a split at each checkpoint
+        );
+    }
+}
+
+// A level's time attack setup, as written in level files
+#[derive(Deserialize, Clone)]
+pub struct TimeAttackDef {
+    pub par: ParTimes,
+    #[serde(default)]
+    pub checkpoints: Vec<Vec2>, // Passed in order, each one recording a split; the end point opens after the last
+}
+
+// Times to beat for each medal, in seconds
+#[derive(Deserialize, Clone, Copy)]
+pub struct ParTimes {
+    pub gold: f32,
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+impl ParTimes {
+    pub fn time(&self, medal: Medal) -> f32 {
+        match medal {
+            Medal::Gold => self.gold,
+            Medal::Silver => self.silver,
+            Medal::Bronze => self.bronze,
+        }
+    }
+
+    // Best medal a run finished in `time` earns
+    pub fn medal(&self, time: f32) -> Option<Medal> {
+        [Medal::Gold, Medal::Silver, Medal::Bronze]
+            .into_iter()
+            .find(|medal| time <= self.time(*medal))
+    }
+}
+
+// Medals for time attack runs, worst first
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    pub fn name(self) -> &'static str {
+        match self {
+            Medal::Gold => "Gold",
+            Medal::Silver => "Silver",
+            Medal::Bronze => "Bronze",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Medal::Gold => Color::rgb(1.0, 0.84, 0.0),
+            Medal::Silver => Color::rgb(0.75, 0.75, 0.8),
+            Medal::Bronze => Color::rgb(0.8, 0.5, 0.2),
+        }
+    }
+}
+
+// A finished time attack run: its time, the time at each checkpoint and the medal it earned
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BestRun {
+    pub time: f32,
+    pub splits: Vec<f32>,
+    pub medal: Option<Medal>,
+}
+
+// Time at each checkpoint passed so far; only present in time attack
+#[derive(Resource)]
+pub struct Splits {
+    pub times: Vec<f32>,
+    pub checkpoints: usize, // Checkpoints along the level
+}
+
+impl Splits {
+    // True once every checkpoint has been passed, so the end point counts
+    pub fn route_done(&self) -> bool {
+        self.times.len() >= self.checkpoints
+    }
+}
+
+// How the last time attack run compared, set when it finishes for the results screen
+#[derive(Resource)]
+pub struct TimeAttackResult {
+    pub run: BestRun,
+    pub previous: Option<BestRun>, // Best run before this one
+}
+
+impl TimeAttackResult {
+    pub fn new_best(&self) -> bool {
+        self.previous.as_ref().map_or(true, |previous| self.run.time < previous.time)
+    }
+}
+
+// A checkpoint marker, numbered from 0 in the order it has to be passed
+#[derive(Component)]
+pub struct Checkpoint(pub usize);
+
+// System to lay out the level's checkpoints in time attack
+pub fn start_time_attack(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    current_level: Res<CurrentLevel>,
+    mode: Res<GameMode>,
+) {
+    commands.remove_resource::<TimeAttackResult>();
+    if *mode != GameMode::TimeAttack {
+        commands.remove_resource::<Splits>();
+        return;
+    }
+
+    let checkpoints = levels.0[current_level.0]
+        .time_attack
+        .as_ref()
+        .map(|time_attack| time_attack.checkpoints.as_slice())
+        .unwrap_or_default();
+    commands.insert_resource(Splits {
+        times: Vec::new(),
+        checkpoints: checkpoints.len(),
+    });
+
+    let text_style = TextStyle {
+        font: asset_server.load(FONT_PATH),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+    for (index, position) in checkpoints.iter().enumerate() {
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: if index == 0 { NEXT_CHECKPOINT } else { LATER_CHECKPOINT },
+                        custom_size: Some(Vec2::splat(CHECKPOINT_SIZE)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(position.extend(-0.5)),
+                    ..Default::default()
+                },
+                Checkpoint(index),
+                LevelEntity,
+            ))
+            .with_children(|checkpoint| {
+                checkpoint.spawn(Text2dBundle {
+                    text: Text::from_section((index + 1).to_string(), text_style.clone()),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..Default::default()
+                });
+            });
+    }
+}
+
+// System to record a split when the ship passes the next checkpoint, and light up the one after it
+pub fn pass_checkpoints(
+    timer: Res<GameTimer>,
+    ships: Query<&Transform, With<Ship>>,
+    mut checkpoints: Query<(&Checkpoint, &Transform, &mut Sprite), Without<Ship>>,
+    mut splits: ResMut<Splits>,
+    config: Res<GameConfig>,
+) {
+    let (Some(time), false) = (timer.0, timer.1) else {
+        return;
+    };
+    let next = splits.times.len();
+    let passed = checkpoints.iter().any(|(checkpoint, transform, _)| {
+        checkpoint.0 == next
+            && ships.iter().any(|ship| {
+                ship.translation.truncate().distance(transform.translation.truncate()) < config.time_attack.checkpoint_radius
+            })
+    });
+    if !passed {
+        return;
+    }
+
+    splits.times.push(time);
+    for (checkpoint, _, mut sprite) in checkpoints.iter_mut() {
+        sprite.color = match checkpoint.0.cmp(&splits.times.len()) {
+            std::cmp::Ordering::Less => PASSED_CHECKPOINT,
+            std::cmp::Ordering::Equal => NEXT_CHECKPOINT,
+            std::cmp::Ordering::Greater => LATER_CHECKPOINT,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn medals_are_earned_at_their_par_time_and_not_after() {
+        let par = ParTimes {
+            gold: 30.0,
+            silver: 45.0,
+            bronze: 60.0,
+        };
+        assert_eq!(par.medal(29.9), Some(Medal::Gold));
+        assert_eq!(par.medal(30.0), Some(Medal::Gold));
+        assert_eq!(par.medal(30.1), Some(Medal::Silver));
+        assert_eq!(par.medal(45.0), Some(Medal::Silver));
+        assert_eq!(par.medal(60.0), Some(Medal::Bronze));
+        assert_eq!(par.medal(60.1), None);
+    }
+
+    #[test]
+    fn only_a_strictly_faster_run_is_a_new_best() {
+        let run = BestRun {
+            time: 40.0,
+            splits: vec![10.0, 25.0],
+            medal: Some(Medal::Silver),
+        };
+        let mut result = TimeAttackResult {
+            run: run.clone(),
+            previous: None,
+        };
+        assert!(result.new_best());
+
+        result.previous = Some(BestRun { time: 40.0, ..run.clone() });
+        assert!(!result.new_best());
+        result.previous = Some(BestRun { time: 40.5, ..run.clone() });
+        assert!(result.new_best());
+        result.previous = Some(BestRun { time: 39.5, ..run });
+        assert!(!result.new_best());
+    }
+}
@@ -0,0 +1,232 @@
+// ui.rs
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+pub const FONT_PATH: &str = "fonts/FiraSans-Bold.ttf";
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.25);
+const FOCUSED_BUTTON: Color = Color::rgb(0.3, 0.3, 0.55);
+const PRESSED_BUTTON: Color = Color::rgb(0.45, 0.45, 0.8);
+
+// Plugin with the shared menu widgets: focus handling, navigation and button colors
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_event::<ButtonActivated>()
+            .add_systems(
+                PreUpdate,
+                (
+                    menu_mouse,          // Focus and activate buttons with the mouse
+                    menu_navigation,     // Move focus and activate buttons with keyboard/gamepad
+                    update_button_colors, // Highlight the focused button
+                )
+                    .chain()
+                    .after(bevy::ui::UiSystem::Focus),
+            );
+    }
+}
+
+// Button taking part in keyboard/gamepad navigation, ordered top-to-bottom by the value
+#[derive(Component)]
+pub struct MenuButton(pub usize);
+
+// Currently focused menu button
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub Option<Entity>);
+
+// Event fired when a menu button is activated by mouse, keyboard or gamepad
+#[derive(Event)]
+pub struct ButtonActivated(pub Entity);
+
+// Keyboard and gamepad input as seen by menus
+#[derive(SystemParam)]
+pub struct MenuInput<'w> {
+    keyboard: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+}
+
+impl MenuInput<'_> {
+    fn gamepad_just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads
+            .iter()
+            .any(|gamepad| self.gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    pub fn up(&self) -> bool {
+        self.keyboard.any_just_pressed([KeyCode::Up, KeyCode::W])
+            || self.gamepad_just_pressed(GamepadButtonType::DPadUp)
+    }
+
+    pub fn down(&self) -> bool {
+        self.keyboard.any_just_pressed([KeyCode::Down, KeyCode::S])
+            || self.gamepad_just_pressed(GamepadButtonType::DPadDown)
+    }
+
+    pub fn left(&self) -> bool {
+        self.keyboard.just_pressed(KeyCode::Left) || self.gamepad_just_pressed(GamepadButtonType::DPadLeft)
+    }
+
+    pub fn right(&self) -> bool {
+        self.keyboard.just_pressed(KeyCode::Right) || self.gamepad_just_pressed(GamepadButtonType::DPadRight)
+    }
+
+    pub fn confirm(&self) -> bool {
+        self.keyboard.any_just_pressed([KeyCode::Return, KeyCode::Space])
+            || self.gamepad_just_pressed(GamepadButtonType::South)
+    }
+
+    pub fn back(&self) -> bool {
+        self.keyboard.just_pressed(KeyCode::Escape) || self.gamepad_just_pressed(GamepadButtonType::East)
+    }
+
+    // Esc on the keyboard or Start on any gamepad
+    pub fn pause(&self) -> bool {
+        self.keyboard.just_pressed(KeyCode::Escape) || self.gamepad_just_pressed(GamepadButtonType::Start)
+    }
+}
+
+// Spawns a full-screen overlay used as the root node of a menu
+pub fn spawn_menu_root(commands: &mut Commands, background: Color) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..Default::default()
+            },
+            background_color: background.into(),
+            z_index: ZIndex::Global(10),
+            ..Default::default()
+        })
+        .id()
+}
+
+// Spawns a menu title
+pub fn spawn_title(parent: &mut ChildBuilder, font: &Handle<Font>, title: &str) {
+    parent.spawn(
+        TextBundle::from_section(
+            title,
+            TextStyle {
+                font: font.clone(),
+                font_size: 60.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::bottom(Val::Px(20.0)),
+            ..Default::default()
+        }),
+    );
+}
+
+// Spawns a menu button with a text label, tagged with `action` so menus can tell buttons apart
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    order: usize,
+    action: impl Bundle,
+) -> Entity {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(320.0),
+                    height: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..Default::default()
+            },
+            MenuButton(order),
+            action,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            ));
+        })
+        .id()
+}
+
+// System to focus buttons on hover and activate them on click
+pub fn menu_mouse(
+    interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<MenuButton>)>,
+    mut focus: ResMut<MenuFocus>,
+    mut activated: EventWriter<ButtonActivated>,
+) {
+    for (entity, interaction) in interactions.iter() {
+        match interaction {
+            Interaction::Pressed => {
+                focus.0 = Some(entity);
+                activated.send(ButtonActivated(entity));
+            }
+            Interaction::Hovered => focus.0 = Some(entity),
+            Interaction::None => {}
+        }
+    }
+}
+
+// System to move the focus between buttons and activate the focused one with keyboard or gamepad
+pub fn menu_navigation(
+    menu_input: MenuInput,
+    buttons: Query<(Entity, &MenuButton)>,
+    mut focus: ResMut<MenuFocus>,
+    mut activated: EventWriter<ButtonActivated>,
+) {
+    let mut ordered: Vec<(usize, Entity)> = buttons.iter().map(|(entity, button)| (button.0, entity)).collect();
+    if ordered.is_empty() {
+        focus.0 = None;
+        return;
+    }
+    ordered.sort();
+
+    // Fall back to the first button when the focused one went away (e.g. a new menu was opened)
+    let mut index = focus
+        .0
+        .and_then(|focused| ordered.iter().position(|(_, entity)| *entity == focused))
+        .unwrap_or(0);
+
+    if menu_input.up() {
+        index = (index + ordered.len() - 1) % ordered.len();
+    }
+    if menu_input.down() {
+        index = (index + 1) % ordered.len();
+    }
+    focus.0 = Some(ordered[index].1);
+
+    if menu_input.confirm() {
+        activated.send(ButtonActivated(ordered[index].1));
+    }
+}
+
+// System to color buttons based on focus and mouse interaction
+pub fn update_button_colors(
+    focus: Res<MenuFocus>,
+    mut buttons: Query<(Entity, &Interaction, &mut BackgroundColor), With<MenuButton>>,
+) {
+    for (entity, interaction, mut color) in buttons.iter_mut() {
+        *color = if *interaction == Interaction::Pressed {
+            PRESSED_BUTTON.into()
+        } else if focus.0 == Some(entity) {
+            FOCUSED_BUTTON.into()
+        } else {
+            NORMAL_BUTTON.into()
+        };
+    }
+}
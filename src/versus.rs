@@ -0,0 +1,174 @@
+// versus.rs
+use bevy::prelude::*;
+use crate::animation::Animations;
+use crate::component::{Faction, GameTimer, Invulnerable, Laser, Ship};
+use crate::config::GameConfig;
+use crate::profile::{LifeLost, PlayerProfile};
+use crate::results::RunOutcome;
+use crate::state::{AppState, GameMode, GameplaySet};
+use crate::system::{destroy_ship, laser_hit, setup, spawn_fireball, LaserImpact};
+
+// Tint a ship fades to as its hull is shot away
+const DAMAGED_COLOR: Color = Color::rgb(1.0, 0.15, 0.15);
+
+// Plugin for versus: players' lasers hurt each other's ships, with spawn protection and a round timer
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), start_round.before(setup)).add_systems(
+            Update,
+            (
+                arm_ships,              // Ships spawn with a full hull and spawn protection
+                hit_rival_ships,        // Lasers wear down the other players' hulls
+                tint_damaged_ships,     // Ships redden as their hull goes
+                expire_invulnerability, // Protected ships blink until the protection wears off
+                end_round,              // Last ship standing, or the best placed when time runs out, wins
+            )
+                .run_if(resource_exists::<Round>())
+                .in_set(GameplaySet),
+        );
+    }
+}
+
+// Time left in a versus round; only present in versus
+#[derive(Resource)]
+pub struct Round(pub Timer);
+
+// How the last versus round ended, for the results screen
+#[derive(Resource)]
+pub struct RoundResult {
+    pub winner: Option<usize>, // `None` for a draw
+    pub time_up: bool,         // True if the round timer decided it
+}
+
+// Laser hits a versus ship can still take before it is destroyed
+#[derive(Component)]
+pub struct Hull(pub u32);
+
+// System to give every player the round's lives and start the round timer in versus
+pub fn start_round(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    mut profile: ResMut<PlayerProfile>,
+    config: Res<GameConfig>,
+) {
+    commands.remove_resource::<RoundResult>();
+    if *mode != GameMode::Versus {
+        commands.remove_resource::<Round>();
+        return;
+    }
+    for stats in profile.players.iter_mut() {
+        stats.lives = config.versus.lives;
+    }
+    commands.insert_resource(Round(Timer::from_seconds(config.versus.round_seconds, TimerMode::Once)));
+}
+
+// System to give new and respawned ships a full hull and a moment of spawn protection
+pub fn arm_ships(mut commands: Commands, ships: Query<Entity, Added<Ship>>, config: Res<GameConfig>) {
+    for entity in ships.iter() {
+        commands.entity(entity).insert((
+            Hull(config.versus.hull),
+            Invulnerable(Timer::from_seconds(config.versus.invulnerable_seconds, TimerMode::Once)),
+        ));
+    }
+}
+
+// System to damage ships hit by another player's laser, destroying them for the shooter's score once their hull is gone
+pub fn hit_rival_ships(
+    mut commands: Commands,
+    animations: Res<Animations>,
+    lasers: Query<(Entity, &Transform, &Laser)>,
+    mut ships: Query<(Entity, &Ship, &Transform, &mut Hull), Without<Invulnerable>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut life_lost: EventWriter<LifeLost>,
+    mut laser_impact: EventWriter<LaserImpact>,
+    config: Res<GameConfig>,
+) {
+    for (laser_entity, laser_transform, laser) in lasers.iter() {
+        let Faction::Player(shooter) = laser.faction else {
+            continue;
+        };
+        let hit = ships.iter_mut().find(|(_, ship, transform, hull)| {
+            ship.0 != shooter
+                && hull.0 > 0
+                && transform.translation.distance(laser_transform.translation) < config.collision_radius
+        });
+        let Some((ship_entity, ship, transform, mut hull)) = hit else {
+            continue;
+        };
+        laser_hit(&mut commands, &animations, &mut laser_impact, laser_entity, laser_transform);
+        hull.0 -= 1;
+        if hull.0 == 0 {
+            spawn_fireball(&mut commands, &animations, transform.translation);
+            destroy_ship(&mut commands, &mut profile, &mut life_lost, ship_entity, ship.0);
+            let stats = &mut profile.players[shooter];
+            stats.score += config.versus.score;
+            stats.kills += 1;
+        }
+    }
+}
+
+// System to fade each damaged ship from its player's color toward red
+pub fn tint_damaged_ships(mut ships: Query<(&Ship, &Hull, &mut Sprite), Changed<Hull>>, config: Res<GameConfig>) {
+    for (ship, hull, mut sprite) in ships.iter_mut() {
+        let health = hull.0 as f32 / config.versus.hull.max(1) as f32;
+        sprite.color = Vec4::from(DAMAGED_COLOR).lerp(Vec4::from(ship.color()), health).into();
+    }
+}
+
+// System to blink protected ships and make them vulnerable again once their protection runs out
+pub fn expire_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+) {
+    for (entity, mut invulnerable, mut visibility) in ships.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+        } else {
+            let blink_on = ((invulnerable.0.remaining_secs() * 8.0) as u32) % 2 == 0;
+            *visibility = if blink_on { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
+
+// System to end the round once one player is left with lives, or when the timer runs out; then the player
+// with the most lives wins, with the hull left and then the score breaking ties
+pub fn end_round(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut round: ResMut<Round>,
+    profile: Res<PlayerProfile>,
+    ships: Query<(&Ship, &Hull)>,
+    mut game_timer: ResMut<GameTimer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    round.0.tick(time.delta());
+    let standing: Vec<usize> = (0..profile.players.len()).filter(|player| profile.players[*player].lives > 0).collect();
+    let time_up = round.0.finished();
+    if standing.len() > 1 && !time_up {
+        return;
+    }
+
+    let winner = if standing.len() > 1 {
+        let placing = |player: usize| {
+            let hull = ships.iter().find(|(ship, _)| ship.0 == player).map_or(0, |(_, hull)| hull.0);
+            let stats = &profile.players[player];
+            (stats.lives, hull, stats.score)
+        };
+        let best = standing.iter().map(|player| placing(*player)).max();
+        let leaders: Vec<usize> = standing.into_iter().filter(|player| Some(placing(*player)) == best).collect();
+        (leaders.len() == 1).then(|| leaders[0])
+    } else {
+        standing.first().copied()
+    };
+
+    // Freeze the arena and show who won
+    game_timer.1 = true;
+    commands.insert_resource(RoundResult { winner, time_up });
+    commands.insert_resource(RunOutcome::GameOver);
+    next_state.set(AppState::Results);
+}
@@ -0,0 +1,194 @@
+// wave.rs
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::component::{BoxEntity, GameTimer, Ship};
+use crate::config::{GameConfig, WaveConfig};
+use crate::difficulty::AdaptiveDifficulty;
+use crate::display::ViewBounds;
+use crate::enemy::{spawn_enemy, Enemy, EnemyKind, EnemySpawn};
+use crate::level::WorldBounds;
+use crate::profile::PlayerProfile;
+use crate::settings::Settings;
+use crate::state::GameplaySet;
+use crate::system::{nearest_ship, spawn_box};
+
+// Plugin for waves of boxes and enemies arriving from the screen edges
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            spawn_waves.run_if(resource_exists::<WaveSpawner>()).in_set(GameplaySet), // Send in the next wave when it is due
+        );
+    }
+}
+
+// How a level's waves grow, as written in level files; every field may be left out
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WaveDef {
+    pub interval: f32,     // Seconds before the first wave
+    pub min_interval: f32, // Waves never come quicker than this, before difficulty
+    pub boxes: u32,        // Boxes in the first wave
+    pub box_growth: f32,   // Extra boxes with each wave
+    pub enemies: u32,      // Enemies in the first wave
+    pub enemy_growth: f32, // Extra enemies with each wave
+    pub max_alive: usize,  // Waves are cut short once this many boxes and enemies are out
+}
+
+impl Default for WaveDef {
+    fn default() -> Self {
+        WaveDef {
+            interval: 15.0,
+            min_interval: 5.0,
+            boxes: 4,
+            box_growth: 1.0,
+            enemies: 0,
+            enemy_growth: 0.34,
+            max_alive: 100,
+        }
+    }
+}
+
+impl WaveDef {
+    // Checks for intervals that would send in a wave every frame; returns the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("interval", self.interval), ("min_interval", self.min_interval)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("`waves.{}` must be above 0, not {}", name, value));
+            }
+        }
+        Ok(())
+    }
+
+    // Seconds between wave `wave` (counted from 0) and the next, at the given spawn rate
+    fn interval(&self, wave: u32, rate: f32, config: &WaveConfig) -> f32 {
+        (self.interval * config.interval_decay.powi(wave as i32)).max(self.min_interval) / rate
+    }
+
+    fn boxes(&self, wave: u32, rate: f32) -> usize {
+        ((self.boxes as f32 + self.box_growth * wave as f32) * rate) as usize
+    }
+
+    fn enemies(&self, wave: u32, rate: f32) -> usize {
+        ((self.enemies as f32 + self.enemy_growth * wave as f32) * rate) as usize
+    }
+}
+
+// Progress through the current level's waves; only present while the level has waves
+#[derive(Resource)]
+pub struct WaveSpawner {
+    pub def: WaveDef,
+    pub wave: u32,       // Waves sent in so far
+    pub until_next: f32, // Seconds until the next wave
+}
+
+impl WaveSpawner {
+    pub fn new(def: WaveDef) -> Self {
+        WaveSpawner {
+            until_next: def.interval,
+            def,
+            wave: 0,
+        }
+    }
+
+    pub fn snapshot(&self) -> WaveSnapshot {
+        WaveSnapshot {
+            wave: self.wave,
+            until_next: self.until_next,
+        }
+    }
+}
+
+// Saved progress through the waves
+#[derive(Serialize, Deserialize)]
+pub struct WaveSnapshot {
+    pub wave: u32,
+    pub until_next: f32,
+}
+
+// System to send in the next wave once it is due, from just outside the view
+pub fn spawn_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_timer: Res<GameTimer>,
+    asset_server: Res<AssetServer>,
+    mut spawner: ResMut<WaveSpawner>,
+    bounds: Res<WorldBounds>,
+    view: Res<ViewBounds>,
+    cameras: Query<&Transform, With<Camera2d>>,
+    ships: Query<&Transform, With<Ship>>,
+    boxes: Query<(), With<BoxEntity>>,
+    enemies: Query<(), With<Enemy>>,
+    (profile, settings, adaptive): (Res<PlayerProfile>, Res<Settings>, Res<AdaptiveDifficulty>),
+    config: Res<GameConfig>,
+) {
+    // Waves wait for the player to set off and stop once the run is over
+    if game_timer.0.is_none() || game_timer.1 {
+        return;
+    }
+    spawner.until_next -= time.delta_seconds();
+    if spawner.until_next > 0.0 {
+        return;
+    }
+    // Hold the wave back while no ship is out there
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    if ships.is_empty() {
+        return;
+    }
+
+    // The difficulty preset, and adaptive difficulty if it's on, scale both the waves and the time between them
+    let rate = adaptive.spawn_rate(profile.difficulty, &settings, &config.difficulty);
+    let wave = spawner.wave;
+    spawner.wave += 1;
+    spawner.until_next += spawner.def.interval(spawner.wave, rate, &config.waves);
+
+    let mut room = spawner.def.max_alive.saturating_sub(boxes.iter().count() + enemies.iter().count());
+    let box_count = spawner.def.boxes(wave, rate).min(room);
+    room -= box_count;
+    let enemy_count = spawner.def.enemies(wave, rate).min(room);
+
+    let mut rng = rand::thread_rng();
+    let center = camera.translation.truncate();
+    // Everything in the wave heads for the ship nearest to where it comes in
+    let target = |position| nearest_ship(ships.iter(), position).unwrap_or(center);
+
+    let box_handle = asset_server.load("box.png");
+    for _ in 0..box_count {
+        let position = edge_position(&mut rng, center, view.half_size + config.waves.spawn_margin, &bounds);
+        let spread = config.waves.box_spread;
+        let heading = Vec2::from_angle(rng.gen_range(-spread..=spread)).rotate(target(position) - position);
+        spawn_box(&mut commands, box_handle.clone(), position.extend(0.0), heading.normalize_or_zero().extend(0.0));
+    }
+
+    let gunner_share = (wave as f32 * config.waves.gunner_share_per_wave).min(config.waves.max_gunner_share);
+    for _ in 0..enemy_count {
+        let position = edge_position(&mut rng, center, view.half_size + config.waves.spawn_margin, &bounds);
+        let kind = if rng.gen::<f32>() < gunner_share { EnemyKind::Gunner } else { EnemyKind::Chaser };
+        // Wave enemies patrol toward where the nearest ship was, so they soon come across it
+        let spawn = EnemySpawn {
+            kind,
+            position,
+            patrol: target(position) - position,
+        };
+        let health = config.enemies.kind(kind).health;
+        spawn_enemy(&mut commands, &asset_server, &config.enemies, &spawn, position, health, None);
+    }
+}
+
+// Picks a point `reach` from the center on a side that still lies inside the world; when the point
+// would be past every side, it is pulled back inside the world instead
+fn edge_position(rng: &mut impl Rng, center: Vec2, reach: Vec2, bounds: &WorldBounds) -> Vec2 {
+    let sides = [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y];
+    let open: Vec<Vec2> = sides
+        .into_iter()
+        .filter(|side| bounds.contains((center + *side * reach).extend(0.0), 0.0))
+        .collect();
+    let side = if open.is_empty() { sides[rng.gen_range(0..sides.len())] } else { open[rng.gen_range(0..open.len())] };
+    let along = side.perp() * side.perp().abs().dot(reach) * rng.gen_range(-1.0..1.0);
+    bounds.clamp((center + side * reach + along).extend(0.0)).truncate()
+}